@@ -1,10 +1,20 @@
-// benchmarks/time_format.rs : evaluates costs of `nanoseconds_to_string()`
+// benchmarks/time_format.rs : evaluates costs of `nanoseconds_to_string()`,
+// and of the non-allocating `write_nanoseconds()` and
+// `format_nanoseconds_into()`
 
 #![allow(non_snake_case)]
 
-use std::hint::black_box;
+use std::{
+    fmt::Write as _,
+    hint::black_box,
+};
 
-use diagnosticism::nanoseconds_to_string;
+use diagnosticism::{
+    format_nanoseconds_into,
+    nanoseconds_to_string,
+    write_nanoseconds,
+    DurationFormat,
+};
 
 use criterion::{
     criterion_group,
@@ -111,6 +121,79 @@ pub fn BENCHMARK_nanoseconds_to_string_mixed_workload(c : &mut Criterion) {
 }
 
 
+/// Compares the ways of appending a formatted duration to a log line
+/// (whose capacity is retained between iterations): via the
+/// [`diagnosticism::NanosecondsStr`] obtained from
+/// `nanoseconds_to_string()`; directly, via `write_nanoseconds()`; and
+/// via a stack buffer filled by `format_nanoseconds_into()`.
+pub fn BENCHMARK_append_to_log_line(c : &mut Criterion) {
+    for (nanoseconds, label) in [(6_789, "6.789 µs"), (123_456_789, "123.4 ms")] {
+        let mut line = String::with_capacity(64);
+
+        c.bench_function(&format!("append via `nanoseconds_to_string()` [{label}]"), |b| {
+            b.iter(|| {
+                line.clear();
+
+                let _ = write!(line, "{}", nanoseconds_to_string(black_box(nanoseconds), black_box("")));
+
+                black_box(line.len())
+            })
+        });
+
+        c.bench_function(&format!("append via `write_nanoseconds()` [{label}]"), |b| {
+            b.iter(|| {
+                line.clear();
+
+                let _ = write_nanoseconds(&mut line, black_box(nanoseconds), black_box(""));
+
+                black_box(line.len())
+            })
+        });
+
+        c.bench_function(&format!("append via `format_nanoseconds_into()` [{label}]"), |b| {
+            b.iter(|| {
+                let mut buf = [0u8; DurationFormat::MAX_LEN];
+
+                line.clear();
+
+                let len = format_nanoseconds_into(&mut buf, black_box(nanoseconds), black_box(""));
+
+                line.push_str(std::str::from_utf8(&buf[..len]).unwrap());
+
+                black_box(line.len())
+            })
+        });
+    }
+}
+
+
+/// Compares the formatting of a duration into a byte buffer, with the
+/// format specification parsed on each call or once beforehand.
+pub fn BENCHMARK_format_into_buffer(c : &mut Criterion) {
+    let df = DurationFormat::from_spec("10.3ms").unwrap();
+
+    c.bench_function("`format_nanoseconds_into()` [\"10.3ms\"]", |b| {
+        b.iter(|| {
+            let mut buf = [0u8; DurationFormat::MAX_LEN];
+
+            let len = format_nanoseconds_into(&mut buf, black_box(123_456_789), black_box("10.3ms"));
+
+            black_box(buf[..len].len())
+        })
+    });
+
+    c.bench_function("`DurationFormat::format_into()` [\"10.3ms\"]", |b| {
+        b.iter(|| {
+            let mut buf = [0u8; DurationFormat::MAX_LEN];
+
+            let len = df.format_into(&mut buf, black_box(123_456_789));
+
+            black_box(buf[..len].len())
+        })
+    });
+}
+
+
 criterion_group!(
     benches,
     BENCHMARK_nanoseconds_to_string_zero,
@@ -121,5 +204,7 @@ criterion_group!(
     BENCHMARK_nanoseconds_to_string_negative,
     BENCHMARK_nanoseconds_to_string_explicit_plus,
     BENCHMARK_nanoseconds_to_string_mixed_workload,
+    BENCHMARK_append_to_log_line,
+    BENCHMARK_format_into_buffer,
 );
 criterion_main!(benches);
@@ -0,0 +1,665 @@
+// src/diagnostics/atomic_doomgram.rs : `AtomicDoomGram`
+
+use super::{
+    doomgram::{
+        DoomGram,
+        NUM_BUCKETS,
+    },
+    gram_utils,
+};
+
+use std::{
+    hint as std_hint,
+    sync::atomic::{
+        AtomicBool,
+        AtomicU64,
+        AtomicUsize,
+        Ordering,
+    },
+    thread as std_thread,
+    time::Duration,
+};
+
+
+/// The number of failed snapshot attempts after which
+/// [`AtomicDoomGram::snapshot()`] yields the thread rather than spinning.
+const SNAPSHOT_SPIN_LIMIT : usize = 64;
+
+/// The number of failed snapshot attempts after which
+/// [`AtomicDoomGram::snapshot()`] holds new pushes until it succeeds.
+const SNAPSHOT_ATTEMPT_LIMIT : usize = 128;
+
+
+/// Thread-safe, lock-free equivalent of [`DoomGram`].
+///
+/// All `push_event_*()` methods take `&self`, so a single instance may be
+/// shared (e.g. via [`std::sync::Arc`], or as a `static`) between many
+/// threads without wrapping it in a [`std::sync::Mutex`]. It maintains the
+/// same twelve order-of-magnitude buckets, min/max, total, sum of squares,
+/// and overflow tracking as [`DoomGram`].
+///
+/// To report, take a [`Self::snapshot()`], which is a plain [`DoomGram`]
+/// on which [`DoomGram::to_strip()`], [`DoomGram::to_mmm()`], and
+/// [`DoomGram::to_nmmm()`] may be called.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::AtomicDoomGram;
+///
+/// use std::{
+///     sync::Arc,
+///     thread,
+/// };
+///
+/// let adg = Arc::new(AtomicDoomGram::default());
+///
+/// let handles : Vec<_> = (0..4)
+///     .map(|_| {
+///         let adg = Arc::clone(&adg);
+///
+///         thread::spawn(move || {
+///             adg.push_event_time_us(92);
+///         })
+///     })
+///     .collect();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+///
+/// let dg = adg.snapshot();
+///
+/// assert_eq!("4:92µs", dg.to_nmmm());
+/// assert_eq!("____a_______", dg.to_strip());
+/// ```
+#[derive(Debug)]
+pub struct AtomicDoomGram {
+    /// Incremented by each writer before it updates any other field.
+    num_pushes_begun :     AtomicUsize,
+    /// Incremented by each writer after it has updated all other fields.
+    num_pushes_completed : AtomicUsize,
+    /// The number of snapshots that, having failed repeatedly, are holding
+    /// new pushes until they succeed.
+    num_snapshots_held :   AtomicUsize,
+
+    event_count :          AtomicUsize,
+
+    event_time_total :     AtomicU64,
+    /// The low and high words of the (`u128`) sum of squares, between
+    /// which a writer propagates the carry.
+    event_time_sum_sq_lo : AtomicU64,
+    event_time_sum_sq_hi : AtomicU64,
+    has_overflowed :       AtomicBool,
+
+    /// `u64::MAX` when no events have been recorded.
+    min_event_time :       AtomicU64,
+    max_event_time :       AtomicU64,
+
+    buckets :              [AtomicU64; NUM_BUCKETS],
+}
+
+// API functions
+
+impl AtomicDoomGram {
+    /// Creates a new, empty, instance.
+    pub fn new() -> Self {
+        Self {
+            num_pushes_begun : AtomicUsize::new(0),
+            num_pushes_completed : AtomicUsize::new(0),
+            num_snapshots_held : AtomicUsize::new(0),
+            event_count : AtomicUsize::new(0),
+            event_time_total : AtomicU64::new(0),
+            event_time_sum_sq_lo : AtomicU64::new(0),
+            event_time_sum_sq_hi : AtomicU64::new(0),
+            has_overflowed : AtomicBool::new(false),
+            min_event_time : AtomicU64::new(u64::MAX),
+            max_event_time : AtomicU64::new(0),
+            buckets : Default::default(),
+        }
+    }
+}
+
+// Mutating methods
+
+impl AtomicDoomGram {
+    /// Clears the instance, resetting all values to the equivalent of a
+    /// newly constructed instance.
+    ///
+    /// # Note:
+    /// This requires exclusive access, since a reset that raced with
+    /// concurrent pushes could not be made consistent without locking.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Pushes an event with the given [`Duration`].
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &self,
+        duration : Duration,
+    ) -> bool {
+        self.push_event_time_ns(duration.as_nanos() as u64)
+    }
+
+    /// Pushes an event with the given number of nanoseconds.
+    pub fn push_event_time_ns(
+        &self,
+        time_in_ns : u64,
+    ) -> bool {
+        while 0 != self.num_snapshots_held.load(Ordering::SeqCst) {
+            std_thread::yield_now();
+        }
+
+        self.num_pushes_begun.fetch_add(1, Ordering::SeqCst);
+
+        let r = self.push_event_time_ns_(time_in_ns);
+
+        self.num_pushes_completed.fetch_add(1, Ordering::SeqCst);
+
+        r
+    }
+
+    /// Pushes an event with the given number of microseconds.
+    pub fn push_event_time_us(
+        &self,
+        time_in_us : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * time_in_us)
+    }
+
+    /// Pushes an event with the given number of milliseconds.
+    pub fn push_event_time_ms(
+        &self,
+        time_in_ms : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * time_in_ms)
+    }
+
+    /// Pushes an event with the given number of seconds.
+    pub fn push_event_time_s(
+        &self,
+        time_in_s : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * 1_000 * time_in_s)
+    }
+}
+
+// Non-mutating methods
+
+impl AtomicDoomGram {
+    /// Number of events counted.
+    ///
+    /// # Note:
+    /// In the presence of concurrent pushes this may not agree with the
+    /// other values; use [`Self::snapshot()`] for a consistent view.
+    pub fn event_count(&self) -> usize {
+        self.event_count.load(Ordering::SeqCst)
+    }
+
+    /// Indicates whether overflow has occurred.
+    pub fn has_overflowed(&self) -> bool {
+        self.has_overflowed.load(Ordering::SeqCst)
+    }
+
+    /// Takes a consistent snapshot of the instance into a plain
+    /// [`DoomGram`].
+    ///
+    /// The snapshot reflects every push that completed before it was
+    /// taken, and none that started after. If pushes are in progress while
+    /// the values are read, the read is retried (yielding the thread after
+    /// repeated failures); if it fails many times, new pushes are held
+    /// (by yielding) until it succeeds, so that it cannot be starved by
+    /// pushes made without pause.
+    pub fn snapshot(&self) -> DoomGram {
+        for num_attempts in 0..SNAPSHOT_ATTEMPT_LIMIT {
+            if let Some(dg) = self.try_snapshot() {
+                return dg;
+            }
+
+            if num_attempts < SNAPSHOT_SPIN_LIMIT {
+                std_hint::spin_loop();
+            } else {
+                std_thread::yield_now();
+            }
+        }
+
+        self.num_snapshots_held.fetch_add(1, Ordering::SeqCst);
+
+        let dg = loop {
+            if let Some(dg) = self.try_snapshot() {
+                break dg;
+            }
+
+            std_thread::yield_now();
+        };
+
+        self.num_snapshots_held.fetch_sub(1, Ordering::SeqCst);
+
+        dg
+    }
+
+    /// Attempts, once, to take a consistent snapshot of the instance, as
+    /// described for [`Self::snapshot()`], without retrying or holding
+    /// pushes.
+    ///
+    /// # Returns
+    ///
+    /// `None` if pushes were in progress while the values were read.
+    pub fn try_snapshot(&self) -> Option<DoomGram> {
+        let num_completed = self.num_pushes_completed.load(Ordering::SeqCst);
+        let num_begun = self.num_pushes_begun.load(Ordering::SeqCst);
+
+        if num_begun != num_completed {
+            return None;
+        }
+
+        let dg = self.snapshot_relaxed();
+
+        if self.num_pushes_begun.load(Ordering::SeqCst) != num_begun {
+            return None;
+        }
+
+        Some(dg)
+    }
+
+    /// Takes a best-effort snapshot of the instance into a plain
+    /// [`DoomGram`], without retrying or holding pushes.
+    ///
+    /// The snapshot reflects every push that completed before it was
+    /// taken, but may reflect only partially those in progress, so that,
+    /// for example, the sum of the bucket counts may not agree with
+    /// [`DoomGram::event_count()`].
+    pub fn snapshot_relaxed(&self) -> DoomGram {
+        let event_count = self.event_count.load(Ordering::SeqCst);
+        let event_time_total = self.event_time_total.load(Ordering::SeqCst);
+        let event_time_sum_sq_lo = self.event_time_sum_sq_lo.load(Ordering::SeqCst);
+        let event_time_sum_sq_hi = self.event_time_sum_sq_hi.load(Ordering::SeqCst);
+        let has_overflowed = self.has_overflowed.load(Ordering::SeqCst);
+        let min_event_time = self.min_event_time.load(Ordering::SeqCst);
+        let max_event_time = self.max_event_time.load(Ordering::SeqCst);
+
+        let mut buckets = [0u64; NUM_BUCKETS];
+
+        for (bucket, count) in buckets.iter_mut().zip(self.buckets.iter()) {
+            *bucket = count.load(Ordering::SeqCst);
+        }
+
+        let (min_event_time, max_event_time) = if 0 == event_count {
+            (None, None)
+        } else {
+            (Some(min_event_time), Some(max_event_time))
+        };
+
+        DoomGram::from_parts_(
+            event_count,
+            event_time_total,
+            (u128::from(event_time_sum_sq_hi) << 64) | u128::from(event_time_sum_sq_lo),
+            has_overflowed,
+            min_event_time,
+            max_event_time,
+            buckets,
+        )
+    }
+}
+
+// Implementation
+
+impl AtomicDoomGram {
+    fn push_event_time_ns_(
+        &self,
+        time_in_ns : u64,
+    ) -> bool {
+        if self.has_overflowed.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let mut total = self.event_time_total.load(Ordering::SeqCst);
+
+        loop {
+            match total.checked_add(time_in_ns) {
+                Some(new_total) => {
+                    match self.event_time_total.compare_exchange_weak(
+                        total,
+                        new_total,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    ) {
+                        Ok(_) => break,
+                        Err(actual) => total = actual,
+                    }
+                },
+                None => {
+                    self.has_overflowed.store(true, Ordering::SeqCst);
+
+                    return false;
+                },
+            }
+        }
+
+        // another push may have overflowed between the check above and
+        // the update of the total, in which case, as with `DoomGram`, this
+        // push is rejected and records nothing, so its addition is undone
+        if self.has_overflowed.load(Ordering::SeqCst) {
+            self.event_time_total.fetch_sub(time_in_ns, Ordering::SeqCst);
+
+            return false;
+        }
+
+        // cannot overflow, since the total has not (see `GramCore`)
+        let time_sq = u128::from(time_in_ns) * u128::from(time_in_ns);
+        let time_sq_lo = time_sq as u64;
+        let time_sq_hi = (time_sq >> 64) as u64;
+
+        let prev_lo = self.event_time_sum_sq_lo.fetch_add(time_sq_lo, Ordering::SeqCst);
+        let carry = u64::from(prev_lo.overflowing_add(time_sq_lo).1);
+
+        self.event_time_sum_sq_hi.fetch_add(time_sq_hi + carry, Ordering::SeqCst);
+
+        self.min_event_time.fetch_min(time_in_ns, Ordering::SeqCst);
+        self.max_event_time.fetch_max(time_in_ns, Ordering::SeqCst);
+
+        self.event_count.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(index) = gram_utils::doom_bucket_index(time_in_ns) {
+            self.buckets[index].fetch_add(1, Ordering::SeqCst);
+        }
+
+        true
+    }
+}
+
+// Trait implementations
+
+impl Default for AtomicDoomGram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl From<&AtomicDoomGram> for DoomGram {
+    fn from(value : &AtomicDoomGram) -> Self {
+        value.snapshot()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        AtomicDoomGram,
+        DoomGram,
+    };
+
+    use std::{
+        sync::{
+            atomic::{
+                AtomicBool,
+                Ordering,
+            },
+            Arc,
+        },
+        thread as std_thread,
+    };
+
+
+    #[test]
+    fn TEST_AtomicDoomGram_new() {
+        let adg = AtomicDoomGram::new();
+
+        assert_eq!(0, adg.event_count());
+        assert!(!adg.has_overflowed());
+
+        let dg = adg.snapshot();
+
+        assert_eq!(0, dg.event_count());
+        assert_eq!(None, dg.min_event_time());
+        assert_eq!(None, dg.max_event_time());
+
+        assert_eq!("", dg.to_mmm());
+        assert_eq!("0:", dg.to_nmmm());
+        assert_eq!("____________", dg.to_strip());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_Default_IS_EMPTY() {
+        let adg = AtomicDoomGram::default();
+
+        assert!(adg.push_event_time_ns(23));
+
+        let dg = adg.snapshot();
+
+        assert_eq!(Some(23), dg.min_event_time());
+        assert_eq!(Some(23), dg.max_event_time());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_MATCHES_DoomGram() {
+        let adg = AtomicDoomGram::new();
+        let mut expected = DoomGram::default();
+
+        for (adg_r, dg_r) in [
+            (adg.push_event_time_ns(23), expected.push_event_time_ns(23)),
+            (adg.push_event_time_ns(10), expected.push_event_time_ns(10)),
+            (adg.push_event_time_ns(0), expected.push_event_time_ns(0)),
+            (adg.push_event_time_us(7), expected.push_event_time_us(7)),
+            (adg.push_event_time_us(89), expected.push_event_time_us(89)),
+            (adg.push_event_time_ms(248), expected.push_event_time_ms(248)),
+            (adg.push_event_time_s(5), expected.push_event_time_s(5)),
+            (adg.push_event_time_s(309), expected.push_event_time_s(309)),
+        ] {
+            assert_eq!(dg_r, adg_r);
+        }
+
+        let actual = adg.snapshot();
+
+        assert_eq!(expected.event_count(), actual.event_count());
+        assert_eq!(expected.event_time_total(), actual.event_time_total());
+        assert_eq!(expected.min_event_time(), actual.min_event_time());
+        assert_eq!(expected.max_event_time(), actual.max_event_time());
+        assert_eq!(expected.variance(), actual.variance());
+
+        assert_eq!("8:0s-39.28s-309s", actual.to_nmmm());
+        assert_eq!(expected.to_strip(), actual.to_strip());
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_SUM_OF_SQUARES_CARRY() {
+        let adg = AtomicDoomGram::new();
+        let mut expected = DoomGram::default();
+
+        // each square has its low word close to `u64::MAX`
+        for _ in 0..3 {
+            adg.push_event_time_ns(u64::from(u32::MAX));
+            expected.push_event_time_ns(u64::from(u32::MAX));
+        }
+        adg.push_event_time_s(1);
+        expected.push_event_time_s(1);
+
+        assert_eq!(expected, adg.snapshot());
+        assert_eq!(expected.stddev(), adg.snapshot().stddev());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_OVERFLOW() {
+        let adg = AtomicDoomGram::new();
+
+        assert!(adg.push_event_time_us(18_446_744_073_709_550));
+        assert!(adg.push_event_time_us(1));
+        assert!(adg.push_event_time_us(0));
+
+        assert!(!adg.push_event_time_us(1));
+        assert!(adg.has_overflowed());
+
+        // once overflowed, all further pushes are rejected
+        assert!(!adg.push_event_time_ns(0));
+
+        let dg = adg.snapshot();
+
+        assert!(dg.has_overflowed());
+        assert_eq!(3, dg.event_count());
+
+        assert_eq!("OVERFLOW", dg.to_mmm());
+        assert_eq!("3:OVERFLOW", dg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_clear() {
+        let mut adg = AtomicDoomGram::new();
+
+        adg.push_event_time_ms(13);
+        adg.clear();
+
+        assert_eq!(0, adg.event_count());
+        assert_eq!("0:", adg.snapshot().to_nmmm());
+
+        adg.push_event_time_ms(13);
+
+        assert_eq!("1:13ms", adg.snapshot().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_MANY_THREADS() {
+        const NUM_THREADS : u64 = 8;
+        const NUM_EVENTS_PER_THREAD : u64 = 10_000;
+
+        let adg = Arc::new(AtomicDoomGram::new());
+
+        let handles : Vec<_> = (0..NUM_THREADS)
+            .map(|t| {
+                let adg = Arc::clone(&adg);
+
+                std_thread::spawn(move || {
+                    for i in 0..NUM_EVENTS_PER_THREAD {
+                        assert!(adg.push_event_time_ns(1 + t * 1_000 + i % 1_000));
+                    }
+                })
+            })
+            .collect();
+
+        // snapshots taken concurrently must always be internally consistent
+        for _ in 0..100 {
+            let dg = adg.snapshot();
+
+            let num_bucketed : u64 = dg.buckets_().iter().sum();
+
+            assert_eq!(dg.event_count() as u64, num_bucketed);
+        }
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let dg = adg.snapshot();
+
+        assert_eq!((NUM_THREADS * NUM_EVENTS_PER_THREAD) as usize, dg.event_count());
+        assert_eq!(Some(1), dg.min_event_time());
+        assert_eq!(Some(7_000 + 999 + 1), dg.max_event_time());
+        assert_eq!(
+            dg.event_count() as u64,
+            dg.num_events_in_1ns()
+                + dg.num_events_in_10ns()
+                + dg.num_events_in_100ns()
+                + dg.num_events_in_1us(),
+        );
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_snapshot_DURING_CONTINUOUS_PUSHES() {
+        const NUM_THREADS : usize = 2;
+
+        let adg = Arc::new(AtomicDoomGram::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        assert!(adg.push_event_time_ns(123));
+
+        let handles : Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let adg = Arc::clone(&adg);
+                let stop = Arc::clone(&stop);
+
+                std_thread::spawn(move || {
+                    while !stop.load(Ordering::Relaxed) {
+                        assert!(adg.push_event_time_ns(123));
+                    }
+                })
+            })
+            .collect();
+
+        // every snapshot returns, is consistent, and none goes backwards
+        let mut prev_event_count = 0;
+
+        for _ in 0..100 {
+            let dg = adg.snapshot();
+
+            assert_eq!(dg.event_count() as u64, dg.num_events_in_100ns());
+            assert!(dg.event_count() >= prev_event_count);
+            assert_eq!(Some(123), dg.max_event_time());
+
+            prev_event_count = dg.event_count();
+        }
+
+        // relaxed snapshots always return, but need not be consistent
+        for _ in 0..100 {
+            let dg = adg.snapshot_relaxed();
+
+            assert!(dg.event_count() >= prev_event_count);
+        }
+
+        stop.store(true, Ordering::Relaxed);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let dg = adg.snapshot();
+
+        assert_eq!(dg.event_count() as u64, dg.num_events_in_100ns());
+        assert_eq!(Some(123), dg.min_event_time());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_try_snapshot() {
+        let adg = AtomicDoomGram::new();
+
+        adg.push_event_time_us(7);
+
+        assert_eq!(Some(adg.snapshot()), adg.try_snapshot());
+
+        // a push that has begun but not completed, so that no consistent
+        // snapshot may be obtained
+        adg.num_pushes_begun.fetch_add(1, Ordering::SeqCst);
+
+        assert_eq!(None, adg.try_snapshot());
+        assert_eq!("1:7µs", adg.snapshot_relaxed().to_nmmm());
+
+        adg.num_pushes_completed.fetch_add(1, Ordering::SeqCst);
+
+        assert_eq!("1:7µs", adg.try_snapshot().unwrap().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_AtomicDoomGram_PUSH_REJECTED_AFTER_CONCURRENT_OVERFLOW() {
+        let adg = AtomicDoomGram::new();
+
+        assert!(adg.push_event_time_us(5));
+
+        // as if another thread overflowed after this push's initial check
+        adg.has_overflowed.store(true, Ordering::SeqCst);
+
+        assert!(!adg.push_event_time_ns_(1));
+        assert_eq!(1, adg.event_count());
+
+        // the rejected push leaves the total unchanged
+        assert_eq!(5_000, adg.snapshot().event_time_total_raw());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,361 @@
+// src/diagnostics/clock.rs : `Clock` and implementations
+
+use std::{
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        OnceLock,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+
+/// A source of monotonic time, in nanoseconds, from which elapsed times
+/// may be measured by [`doom_scope_with_clock()`][scope] and
+/// [`DoomGram::time_scope_with_clock()`][guard].
+///
+/// The epoch of the returned values is unspecified: only the difference
+/// between two values obtained from the same clock is meaningful.
+///
+/// [scope]: crate::doom_scope_with_clock
+/// [guard]: crate::DoomGram::time_scope_with_clock
+pub trait Clock {
+    /// Obtains the current time, in nanoseconds.
+    fn now_ns(&self) -> u64;
+}
+
+
+/// [`Clock`] that measures wall-clock time using [`Instant`].
+///
+/// This is the clock used by [`doom_scope()`](crate::doom_scope) and
+/// [`DoomGram::time_scope()`](crate::DoomGram::time_scope).
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+pub struct InstantClock;
+
+
+/// [`Clock`] whose time is advanced only by explicit calls, so that
+/// timing-dependent code may be tested deterministically.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     doom_scope_with_clock,
+///     DoomGram,
+///     ManualClock,
+/// };
+///
+/// let clock = ManualClock::new(0);
+/// let mut dg = DoomGram::default();
+///
+/// doom_scope_with_clock(&mut dg, &clock, || clock.advance_ns(1_500));
+///
+/// assert_eq!("1:1.500µs", dg.to_nmmm());
+/// ```
+#[derive(Debug)]
+#[derive(Default)]
+pub struct ManualClock {
+    now_ns : AtomicU64,
+}
+
+
+/// [`Clock`] that measures the CPU time consumed by the calling thread,
+/// obtained from `clock_gettime(CLOCK_THREAD_CPUTIME_ID)`.
+///
+/// Available only on 64-bit Linux.
+///
+/// # Note:
+/// Should `clock_gettime()` fail, which does not happen on a functioning
+/// system, `now_ns()` returns the last value obtained by the calling
+/// thread (or 0), so that no time is measured.
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+pub struct ThreadCpuClock;
+
+
+/// [`Clock`] that measures the CPU time consumed by all threads of the
+/// process, obtained from `clock_gettime(CLOCK_PROCESS_CPUTIME_ID)`.
+///
+/// Available only on 64-bit Linux.
+///
+/// # Note:
+/// Should `clock_gettime()` fail, which does not happen on a functioning
+/// system, `now_ns()` returns the last value obtained (or 0), so that no
+/// time is measured.
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+pub struct ProcessCpuClock;
+
+// API functions
+
+impl ManualClock {
+    /// Creates an instance whose current time is `now_ns`.
+    pub fn new(now_ns : u64) -> Self {
+        Self {
+            now_ns : AtomicU64::new(now_ns),
+        }
+    }
+}
+
+// Mutating methods
+
+impl ManualClock {
+    /// Sets the current time, in nanoseconds.
+    pub fn set_ns(
+        &self,
+        now_ns : u64,
+    ) {
+        self.now_ns.store(now_ns, Ordering::SeqCst);
+    }
+
+    /// Advances the current time by the given number of nanoseconds.
+    pub fn advance_ns(
+        &self,
+        time_in_ns : u64,
+    ) {
+        self.now_ns.fetch_add(time_in_ns, Ordering::SeqCst);
+    }
+
+    /// Advances the current time by the given [`Duration`].
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn advance(
+        &self,
+        duration : Duration,
+    ) {
+        self.advance_ns(duration.as_nanos() as u64);
+    }
+}
+
+// Trait implementations
+
+impl<C : Clock + ?Sized> Clock for &C {
+    fn now_ns(&self) -> u64 {
+        (**self).now_ns()
+    }
+}
+
+
+impl Clock for InstantClock {
+    fn now_ns(&self) -> u64 {
+        static BASE : OnceLock<Instant> = OnceLock::new();
+
+        let base = *BASE.get_or_init(Instant::now);
+
+        base.elapsed().as_nanos() as u64
+    }
+}
+
+
+impl Clock for ManualClock {
+    fn now_ns(&self) -> u64 {
+        self.now_ns.load(Ordering::SeqCst)
+    }
+}
+
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+impl Clock for ThreadCpuClock {
+    fn now_ns(&self) -> u64 {
+        thread_local! {
+            static LAST_NS : AtomicU64 = const { AtomicU64::new(0) };
+        }
+
+        LAST_NS.with(|last_ns| linux_::clock_gettime_ns(linux_::CLOCK_THREAD_CPUTIME_ID, last_ns))
+    }
+}
+
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+impl Clock for ProcessCpuClock {
+    fn now_ns(&self) -> u64 {
+        static LAST_NS : AtomicU64 = AtomicU64::new(0);
+
+        linux_::clock_gettime_ns(linux_::CLOCK_PROCESS_CPUTIME_ID, &LAST_NS)
+    }
+}
+
+
+// Helper functions
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+mod linux_ {
+    use std::{
+        ffi::{
+            c_int,
+            c_long,
+        },
+        sync::atomic::{
+            AtomicU64,
+            Ordering,
+        },
+    };
+
+
+    pub(super) const CLOCK_PROCESS_CPUTIME_ID : c_int = 2;
+    pub(super) const CLOCK_THREAD_CPUTIME_ID : c_int = 3;
+
+    // NOTE: `time_t` is `c_long` only on 64-bit targets (hence the
+    // restriction of the module to them), since 32-bit targets may
+    // instead have a 64-bit `time_t`
+    #[repr(C)]
+    struct Timespec {
+        tv_sec :  c_long,
+        tv_nsec : c_long,
+    }
+
+    extern "C" {
+        fn clock_gettime(
+            clock_id : c_int,
+            tp : *mut Timespec,
+        ) -> c_int;
+    }
+
+    /// Obtains the time of the given clock, in nanoseconds, recording it
+    /// in `last_ns`, from which it is instead obtained should
+    /// `clock_gettime()` fail.
+    pub(super) fn clock_gettime_ns(
+        clock_id : c_int,
+        last_ns : &AtomicU64,
+    ) -> u64 {
+        let mut ts = Timespec {
+            tv_sec :  0,
+            tv_nsec : 0,
+        };
+
+        // SAFETY: `ts` is a valid, writable `struct timespec`
+        let r = unsafe { clock_gettime(clock_id, &mut ts) };
+
+        // the CPU-time clocks of the calling thread/process always exist,
+        // so failure indicates a broken platform, on which the time is
+        // held at the last value, rather than reading a meaningless (zero)
+        // time or panicking within a measurement
+        if 0 != r {
+            return last_ns.load(Ordering::Relaxed);
+        }
+
+        let now_ns = (ts.tv_sec as u64).saturating_mul(1_000_000_000).saturating_add(ts.tv_nsec as u64);
+
+        last_ns.store(now_ns, Ordering::Relaxed);
+
+        now_ns
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        Clock,
+        InstantClock,
+        ManualClock,
+    };
+
+    use std::{
+        thread as std_thread,
+        time::Duration,
+    };
+
+
+    #[test]
+    fn TEST_InstantClock_IS_MONOTONIC() {
+        let clock = InstantClock;
+
+        let t0 = clock.now_ns();
+
+        std_thread::sleep(Duration::from_millis(1));
+
+        let t1 = clock.now_ns();
+
+        assert!(t1 - t0 >= 1_000_000);
+    }
+
+    #[test]
+    fn TEST_ManualClock() {
+        let clock = ManualClock::new(100);
+
+        assert_eq!(100, clock.now_ns());
+
+        clock.advance_ns(23);
+
+        assert_eq!(123, clock.now_ns());
+
+        clock.advance(Duration::from_micros(2));
+
+        assert_eq!(2_123, clock.now_ns());
+
+        clock.set_ns(7);
+
+        assert_eq!(7, Clock::now_ns(&&clock));
+        assert_eq!(0, ManualClock::default().now_ns());
+    }
+
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    #[test]
+    fn TEST_ThreadCpuClock_EXCLUDES_SLEEP() {
+        use super::ThreadCpuClock;
+
+        let clock = ThreadCpuClock;
+
+        let t0 = clock.now_ns();
+
+        std_thread::sleep(Duration::from_millis(20));
+
+        let t1 = clock.now_ns();
+
+        assert!(t1 >= t0);
+        assert!(t1 - t0 < 20_000_000);
+    }
+
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    #[test]
+    fn TEST_ProcessCpuClock_INCLUDES_WORK() {
+        use super::ProcessCpuClock;
+
+        let clock = ProcessCpuClock;
+
+        let t0 = clock.now_ns();
+
+        let mut x = 0u64;
+
+        while clock.now_ns() - t0 < 1_000_000 {
+            x = std::hint::black_box(x.wrapping_add(1));
+        }
+
+        assert!(0 != x);
+    }
+
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    #[test]
+    fn TEST_clock_gettime_ns_FAILURE_HOLDS_LAST_VALUE() {
+        use super::linux_;
+
+        use std::sync::atomic::AtomicU64;
+
+        let last_ns = AtomicU64::new(0);
+
+        let t0 = linux_::clock_gettime_ns(linux_::CLOCK_PROCESS_CPUTIME_ID, &last_ns);
+
+        assert_eq!(t0, linux_::clock_gettime_ns(-1, &last_ns));
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,404 @@
+// src/diagnostics/doom_future.rs : `DoomFuture`
+
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doom_target::DoomTarget,
+};
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{
+        Context,
+        Poll,
+    },
+};
+
+
+/// Future that wraps another future and, upon its completion, records
+/// into a gram the wall-clock time from its first poll, and optionally
+/// records into a second gram the total time spent inside its `poll()`.
+///
+/// Obtained from [`doom_future()`], it is the asynchronous equivalent of
+/// [`doom_scope()`](crate::doom_scope), or, measuring with a given
+/// [`Clock`], from [`doom_future_with_clock()`]. It does not depend on
+/// any particular runtime.
+///
+/// The output is a tuple of the wrapped future's output together with the
+/// measured elapsed time (in nanoseconds). If the future is dropped before
+/// it completes, nothing is recorded.
+#[must_use = "futures do nothing unless polled"]
+pub struct DoomFuture<F, T, P = T, C = InstantClock> {
+    future :          F,
+    target :          Option<T>,
+    poll_target :     Option<P>,
+    clock :           C,
+    first_polled_at : Option<u64>,
+    time_in_poll :    u64,
+}
+
+// API functions
+
+/// Wraps a future such that the wall-clock time from its first poll to its
+/// completion is recorded into `target`.
+///
+/// # Parameters
+///
+/// * `target` — the gram into which the elapsed time is recorded, such as
+///   `&mut DoomGram`, `&AtomicDoomGram`, or `Arc<AtomicDoomGram>`;
+/// * `future` — the future to be timed;
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     doom_future,
+///     AtomicDoomGram,
+/// };
+///
+/// use std::sync::Arc;
+///
+/// async fn handle_request(
+///     dg : Arc<AtomicDoomGram>,
+///     id : u32,
+/// ) -> u32 {
+///     let (result, _elapsed_ns) = doom_future(dg, async move { id * 2 }).await;
+///
+///     result
+/// }
+/// # let _ = handle_request(Arc::new(AtomicDoomGram::new()), 1);
+/// ```
+pub fn doom_future<F, T>(
+    target : T,
+    future : F,
+) -> DoomFuture<F, T>
+where
+    F : Future,
+    T : DoomTarget,
+{
+    doom_future_with_clock(target, InstantClock, future)
+}
+
+/// Like [`doom_future()`], but measures with the given [`Clock`].
+///
+/// # Parameters
+///
+/// * `target` — the gram into which the elapsed time is recorded;
+/// * `clock` — the clock with which the elapsed time, and any time spent
+///   in `poll()`, is measured;
+/// * `future` — the future to be timed;
+pub fn doom_future_with_clock<F, T, C>(
+    target : T,
+    clock : C,
+    future : F,
+) -> DoomFuture<F, T, T, C>
+where
+    F : Future,
+    T : DoomTarget,
+    C : Clock,
+{
+    DoomFuture {
+        future,
+        target : Some(target),
+        poll_target : None,
+        clock,
+        first_polled_at : None,
+        time_in_poll : 0,
+    }
+}
+
+impl<F, T, C> DoomFuture<F, T, T, C>
+where
+    F : Future,
+    T : DoomTarget,
+    C : Clock,
+{
+    /// Additionally records into `poll_target`, upon completion, the total
+    /// time spent inside the wrapped future's `poll()`, which may be
+    /// compared with the elapsed time to distinguish time spent working
+    /// from time spent waiting.
+    pub fn with_poll_gram<P>(
+        self,
+        poll_target : P,
+    ) -> DoomFuture<F, T, P, C>
+    where
+        P : DoomTarget,
+    {
+        DoomFuture {
+            future : self.future,
+            target : self.target,
+            poll_target : Some(poll_target),
+            clock : self.clock,
+            first_polled_at : self.first_polled_at,
+            time_in_poll : self.time_in_poll,
+        }
+    }
+}
+
+// Trait implementations
+
+impl<F, T, P, C> Future for DoomFuture<F, T, P, C>
+where
+    F : Future,
+    T : DoomTarget,
+    P : DoomTarget,
+    C : Clock,
+{
+    type Output = (
+        F::Output, // work_result
+        u64,       // measured_elapsed_time_in_ns
+    );
+
+    fn poll(
+        self : Pin<&mut Self>,
+        cx : &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        // SAFETY: `future` is structurally pinned: it is never moved out
+        // of `self`, and `DoomFuture` implements neither `Drop` nor (other
+        // than by auto-trait, when `F : Unpin`) `Unpin`. The other fields
+        // are not pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+
+        assert!(this.target.is_some(), "`DoomFuture` polled after completion");
+
+        let before = this.clock.now_ns();
+        let first_polled_at = *this.first_polled_at.get_or_insert(before);
+
+        let poll = future.poll(cx);
+
+        let after = this.clock.now_ns();
+
+        this.time_in_poll = this.time_in_poll.saturating_add(after.saturating_sub(before));
+
+        match poll {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(work_result) => {
+                let measured_elapsed_time_in_ns = after.saturating_sub(first_polled_at);
+
+                if let Some(mut target) = this.target.take() {
+                    target.push_event_time_ns(measured_elapsed_time_in_ns);
+                }
+
+                if let Some(mut poll_target) = this.poll_target.take() {
+                    poll_target.push_event_time_ns(this.time_in_poll);
+                }
+
+                Poll::Ready((work_result, measured_elapsed_time_in_ns))
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        doom_future,
+        doom_future_with_clock,
+    };
+
+    use crate::diagnostics::{
+        AtomicDoomGram,
+        DoomGram,
+        ManualClock,
+    };
+
+    use std::{
+        future::{
+            self,
+            Future,
+        },
+        pin::Pin,
+        sync::Arc,
+        task::{
+            Context,
+            Poll,
+            RawWaker,
+            RawWakerVTable,
+            Waker,
+        },
+        thread as std_thread,
+        time::Duration,
+    };
+
+
+    /// Minimal executor: polls the future on the current thread, with a
+    /// no-op waker, until it completes, returning its output and the
+    /// number of polls.
+    fn block_on<F : Future>(future : F) -> (F::Output, usize) {
+        fn clone(_ : *const ()) -> RawWaker {
+            RawWaker::new(std::ptr::null(), &VTABLE)
+        }
+        fn noop(_ : *const ()) {}
+
+        static VTABLE : RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        let mut num_polls = 0;
+
+        loop {
+            num_polls += 1;
+
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return (output, num_polls);
+            }
+        }
+    }
+
+    /// Future that is pending for a given number of polls, sleeping for a
+    /// given time within each poll.
+    struct Yields {
+        remaining :  usize,
+        poll_sleep : Duration,
+    }
+
+    impl Future for Yields {
+        type Output = &'static str;
+
+        fn poll(
+            mut self : Pin<&mut Self>,
+            cx : &mut Context<'_>,
+        ) -> Poll<Self::Output> {
+            std_thread::sleep(self.poll_sleep);
+
+            if 0 == self.remaining {
+                Poll::Ready("done")
+            } else {
+                self.remaining -= 1;
+
+                cx.waker().wake_by_ref();
+
+                Poll::Pending
+            }
+        }
+    }
+
+
+    #[test]
+    fn TEST_doom_future_DoomGram() {
+        let mut dg = DoomGram::default();
+
+        let ((result, elapsed_ns), num_polls) = block_on(doom_future(&mut dg, async { 42 }));
+
+        assert_eq!(42, result);
+        assert_eq!(1, num_polls);
+        assert_eq!(1, dg.event_count());
+        assert_eq!(Some(elapsed_ns), dg.min_event_time());
+    }
+
+    #[test]
+    fn TEST_doom_future_MULTIPLE_POLLS() {
+        let mut dg = DoomGram::default();
+
+        let future = Yields {
+            remaining :  3,
+            poll_sleep : Duration::from_millis(1),
+        };
+
+        let ((result, elapsed_ns), num_polls) = block_on(doom_future(&mut dg, future));
+
+        assert_eq!("done", result);
+        assert_eq!(4, num_polls);
+        assert!(elapsed_ns >= 4_000_000);
+        assert_eq!(1, dg.event_count());
+    }
+
+    #[test]
+    fn TEST_doom_future_AtomicDoomGram() {
+        let adg = AtomicDoomGram::new();
+        let arc_adg = Arc::new(AtomicDoomGram::new());
+
+        block_on(doom_future(&adg, async { 1 }));
+        block_on(doom_future(&adg, async { 2 }));
+        block_on(doom_future(Arc::clone(&arc_adg), async { 3 }));
+
+        assert_eq!(2, adg.event_count());
+        assert_eq!(1, arc_adg.event_count());
+    }
+
+    #[test]
+    fn TEST_doom_future_with_poll_gram() {
+        let mut dg = DoomGram::default();
+        let poll_dg = AtomicDoomGram::new();
+
+        let future = async {
+            Yields {
+                remaining :  2,
+                poll_sleep : Duration::from_millis(1),
+            }
+            .await
+        };
+
+        let ((_, elapsed_ns), _) = block_on(doom_future(&mut dg, future).with_poll_gram(&poll_dg));
+
+        let poll_dg = poll_dg.snapshot();
+
+        assert_eq!(1, dg.event_count());
+        assert_eq!(1, poll_dg.event_count());
+
+        let time_in_poll_ns = poll_dg.min_event_time().unwrap();
+
+        assert!(time_in_poll_ns >= 3_000_000);
+        assert!(time_in_poll_ns <= elapsed_ns);
+    }
+
+    #[test]
+    fn TEST_doom_future_with_clock() {
+        let clock = ManualClock::new(0);
+        let mut dg = DoomGram::default();
+        let poll_dg = AtomicDoomGram::new();
+
+        let mut remaining = 2;
+        let future = future::poll_fn(|cx| {
+            clock.advance_ns(1_500);
+
+            if 0 == remaining {
+                Poll::Ready("done")
+            } else {
+                remaining -= 1;
+
+                cx.waker().wake_by_ref();
+
+                Poll::Pending
+            }
+        });
+
+        let ((result, elapsed_ns), num_polls) = block_on(doom_future_with_clock(&mut dg, &clock, future).with_poll_gram(&poll_dg));
+
+        assert_eq!("done", result);
+        assert_eq!(3, num_polls);
+        assert_eq!(4_500, elapsed_ns);
+        assert_eq!("1:4.500µs", dg.to_nmmm());
+        assert_eq!("1:4.500µs", poll_dg.snapshot().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_doom_future_DROPPED_BEFORE_COMPLETION() {
+        let mut dg = DoomGram::default();
+
+        {
+            let future = doom_future(
+                &mut dg,
+                Yields {
+                    remaining :  1,
+                    poll_sleep : Duration::ZERO,
+                },
+            );
+
+            drop(future);
+        }
+
+        assert_eq!(0, dg.event_count());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,290 @@
+// src/diagnostics/doom_guard.rs : `DoomGuard`
+
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doomgram::DoomGram,
+};
+
+
+/// Scope guard that records the time elapsed since its creation into a
+/// [`DoomGram`] when it is dropped.
+///
+/// Obtained from [`DoomGram::time_scope()`], it offers the same facility
+/// as [`doom_scope()`](crate::doom_scope) without the need for a closure,
+/// and so works naturally with `?`, early returns, and borrows that span
+/// the timed region.
+///
+/// Time is measured by a [`Clock`], which is [`InstantClock`] unless the
+/// guard is obtained from [`DoomGram::time_scope_with_clock()`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::DoomGram;
+///
+/// fn parse_port(
+///     dg : &mut DoomGram,
+///     s : &str,
+/// ) -> Result<u16, std::num::ParseIntError> {
+///     let _guard = dg.time_scope();
+///
+///     let port = s.parse::<u16>()?;
+///
+///     Ok(port)
+/// }
+///
+/// let mut dg = DoomGram::default();
+///
+/// assert!(parse_port(&mut dg, "8080").is_ok());
+/// assert!(parse_port(&mut dg, "http").is_err());
+///
+/// assert_eq!(2, dg.event_count());
+/// ```
+#[must_use = "the elapsed time is recorded when the guard is dropped"]
+pub struct DoomGuard<'a, C : Clock = InstantClock> {
+    dg :    &'a mut DoomGram,
+    timer : GuardTimer_<C>,
+}
+
+
+/// The measurement state common to [`DoomGuard`] and
+/// [`OutcomeDoomGuard`](super::OutcomeDoomGuard): the clock, the time at
+/// which the guard was created, and whether the elapsed time is yet to be
+/// recorded.
+pub(super) struct GuardTimer_<C : Clock> {
+    clock :        C,
+    before :       u64,
+    is_recording : bool,
+}
+
+// API functions
+
+impl<'a, C : Clock> DoomGuard<'a, C> {
+    pub(crate) fn new_(
+        dg : &'a mut DoomGram,
+        clock : C,
+    ) -> Self {
+        Self {
+            dg,
+            timer : GuardTimer_::new_(clock),
+        }
+    }
+}
+
+impl<C : Clock> GuardTimer_<C> {
+    pub(super) fn new_(clock : C) -> Self {
+        let before = clock.now_ns();
+
+        Self {
+            clock,
+            before,
+            is_recording : true,
+        }
+    }
+}
+
+// Mutating methods
+
+impl<C : Clock> DoomGuard<'_, C> {
+    /// Discards the measurement, such that nothing is recorded in the
+    /// gram.
+    pub fn cancel(mut self) {
+        self.timer.cancel_();
+    }
+
+    /// Records the elapsed time into the gram immediately, rather than
+    /// when the guard would otherwise have been dropped, and returns it
+    /// (in nanoseconds).
+    pub fn stop(mut self) -> u64 {
+        self.record_()
+    }
+}
+
+// Non-mutating methods
+
+impl<C : Clock> DoomGuard<'_, C> {
+    /// The time elapsed (in nanoseconds) since the guard was created.
+    pub fn elapsed_ns(&self) -> u64 {
+        self.timer.elapsed_ns_()
+    }
+}
+
+impl<C : Clock> GuardTimer_<C> {
+    pub(super) fn elapsed_ns_(&self) -> u64 {
+        self.clock.now_ns().saturating_sub(self.before)
+    }
+
+    pub(super) fn is_recording_(&self) -> bool {
+        self.is_recording
+    }
+}
+
+// Implementation
+
+impl<C : Clock> DoomGuard<'_, C> {
+    fn record_(&mut self) -> u64 {
+        self.timer.record_(|time_in_ns| {
+            self.dg.push_event_time_ns(time_in_ns);
+        })
+    }
+}
+
+impl<C : Clock> GuardTimer_<C> {
+    pub(super) fn cancel_(&mut self) {
+        self.is_recording = false;
+    }
+
+    /// Obtains the elapsed time and, unless it has already been recorded
+    /// (or the measurement cancelled), passes it to `record`, returning
+    /// it in either case.
+    pub(super) fn record_<F : FnOnce(u64)>(
+        &mut self,
+        record : F,
+    ) -> u64 {
+        let measured_elapsed_time_in_ns = self.elapsed_ns_();
+
+        if self.is_recording {
+            self.is_recording = false;
+
+            record(measured_elapsed_time_in_ns);
+        }
+
+        measured_elapsed_time_in_ns
+    }
+}
+
+// Trait implementations
+
+impl<C : Clock> Drop for DoomGuard<'_, C> {
+    fn drop(&mut self) {
+        if self.timer.is_recording_() {
+            self.record_();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use crate::diagnostics::{
+        DoomGram,
+        ManualClock,
+    };
+
+    use std::{
+        thread as std_thread,
+        time::Duration,
+    };
+
+
+    #[test]
+    fn TEST_DoomGuard_RECORDS_ON_DROP() {
+        let mut dg = DoomGram::default();
+
+        {
+            let _guard = dg.time_scope();
+
+            std_thread::sleep(Duration::from_millis(2));
+        }
+
+        assert_eq!(1, dg.event_count());
+        assert!(dg.min_event_time().unwrap() >= 2_000_000);
+    }
+
+    #[test]
+    fn TEST_DoomGuard_RECORDS_ON_EARLY_RETURN() {
+        fn f(
+            dg : &mut DoomGram,
+            fail : bool,
+        ) -> Result<(), ()> {
+            let _guard = dg.time_scope();
+
+            if fail {
+                return Err(());
+            }
+
+            Ok(())
+        }
+
+        let mut dg = DoomGram::default();
+
+        assert_eq!(Ok(()), f(&mut dg, false));
+        assert_eq!(Err(()), f(&mut dg, true));
+
+        assert_eq!(2, dg.event_count());
+    }
+
+    #[test]
+    fn TEST_DoomGuard_cancel() {
+        let mut dg = DoomGram::default();
+
+        let guard = dg.time_scope();
+
+        guard.cancel();
+
+        assert_eq!(0, dg.event_count());
+        assert_eq!("0:", dg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGuard_stop() {
+        let mut dg = DoomGram::default();
+
+        let guard = dg.time_scope();
+
+        std_thread::sleep(Duration::from_millis(1));
+
+        let elapsed_ns = guard.stop();
+
+        assert!(elapsed_ns >= 1_000_000);
+        assert_eq!(1, dg.event_count());
+        assert_eq!(Some(elapsed_ns), dg.min_event_time());
+        assert_eq!(Some(elapsed_ns), dg.max_event_time());
+    }
+
+    #[test]
+    fn TEST_DoomGuard_elapsed_ns() {
+        let mut dg = DoomGram::default();
+
+        let guard = dg.time_scope();
+
+        std_thread::sleep(Duration::from_millis(1));
+
+        let elapsed_ns = guard.elapsed_ns();
+
+        assert!(elapsed_ns >= 1_000_000);
+        assert!(guard.stop() >= elapsed_ns);
+    }
+
+    #[test]
+    fn TEST_DoomGuard_WITH_ManualClock() {
+        let clock = ManualClock::new(1_000);
+        let mut dg = DoomGram::default();
+
+        {
+            let guard = dg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(3_000_000);
+
+            assert_eq!(3_000_000, guard.elapsed_ns());
+        }
+
+        {
+            let guard = dg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(7);
+
+            assert_eq!(7, guard.stop());
+        }
+
+        assert_eq!("2:7ns-1.500ms-3ms", dg.to_nmmm());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,109 @@
+// src/diagnostics/doom_target.rs : `DoomTarget`
+
+use super::{
+    atomic_doomgram::AtomicDoomGram,
+    doomgram::DoomGram,
+    fine_doomgram::FineDoomGram,
+};
+
+use std::sync::Arc;
+
+
+/// A destination into which measured event times may be recorded, such
+/// as a (mutable reference to a) [`DoomGram`] or a (shared reference to
+/// an) [`AtomicDoomGram`].
+///
+/// This allows facilities such as [`DoomFuture`](crate::DoomFuture) to
+/// record into any kind of gram.
+pub trait DoomTarget {
+    /// Pushes an event with the given number of nanoseconds, returning
+    /// `false` if the gram has overflowed.
+    fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool;
+}
+
+// Trait implementations
+
+impl DoomTarget for &mut DoomGram {
+    fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        DoomGram::push_event_time_ns(self, time_in_ns)
+    }
+}
+
+
+impl DoomTarget for &mut FineDoomGram {
+    fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        FineDoomGram::push_event_time_ns(self, time_in_ns)
+    }
+}
+
+
+impl DoomTarget for &AtomicDoomGram {
+    fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        AtomicDoomGram::push_event_time_ns(self, time_in_ns)
+    }
+}
+
+
+impl DoomTarget for Arc<AtomicDoomGram> {
+    fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        AtomicDoomGram::push_event_time_ns(self, time_in_ns)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::DoomTarget;
+
+    use crate::diagnostics::{
+        AtomicDoomGram,
+        DoomGram,
+        FineDoomGram,
+    };
+
+    use std::sync::Arc;
+
+
+    fn push_7ns<T : DoomTarget>(mut target : T) -> bool {
+        target.push_event_time_ns(7)
+    }
+
+
+    #[test]
+    fn TEST_DoomTarget_IMPLEMENTATIONS() {
+        let mut dg = DoomGram::default();
+        let mut fdg = FineDoomGram::new(2);
+        let adg = AtomicDoomGram::new();
+        let arc_adg = Arc::new(AtomicDoomGram::new());
+
+        assert!(push_7ns(&mut dg));
+        assert!(push_7ns(&mut fdg));
+        assert!(push_7ns(&adg));
+        assert!(push_7ns(Arc::clone(&arc_adg)));
+
+        assert_eq!("1:7ns", dg.to_nmmm());
+        assert_eq!("1:7ns", fdg.to_nmmm());
+        assert_eq!("1:7ns", adg.snapshot().to_nmmm());
+        assert_eq!("1:7ns", arc_adg.snapshot().to_nmmm());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -1,90 +1,45 @@
 // src/diagnostics/doomgram.rs : `DoomGram`
 
-use std::{
-    str as std_str,
-    time::{
-        Duration,
-        Instant,
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doom_guard::DoomGuard,
+    gram_utils::{
+        self,
+        GramCore,
     },
+    time_format::DurationFormat,
 };
 
+use std::{
+    fmt as std_fmt,
+    ops as std_ops,
+    time::Duration,
+};
 
-// TODO: move into a grams-utils place
-
-pub(crate) mod gram_utils {
-
-    pub fn calc_doom(v : u64) -> u32 {
-
-        if v >= 100_000_000 {
-        //    return count_decimal_digits(v);
-        } else {
-            if v >= 10_000 {
-                if v >= 1_000_000 {
-                    if v >= 10_000_000 {
-                        return 8;
-                    } else {
-                        return 7;
-                    }
-                } else {
-                    if v >= 100_000 {
-                        return 6;
-                    } else {
-                        return 5;
-                    }
-                }
-            } else {
-                if v >= 100 {
-                    if v >= 1_000 {
-                        return 4;
-                    } else {
-                        return 3;
-                    }
-                } else {
-                    if v >= 10 {
-                        return 2;
-                    } else {
-                        if v > 0 {
-                            return 1;
-                        } else {
-                            return 0;
-                        }
-                    }
-                }
-            }
-        }
-
-        if 0 == v {
-            0
-        } else {
-            let mut r = 0;
-            let mut v = v;
-
-            while 0 != v {
-                v /= 10;
-                r += 1;
-            }
-
-            r
-        }
-    }
 
-    pub fn gram_doom_to_char(
-        doom : u32,
-        ch_0 : u8,
-        ch_overflow : u8,
-        range : &[u8],
-    ) -> u8 {
-        if 0 == doom {
-            ch_0
-        } else {
-            if doom as usize > range.len() {
-                ch_overflow
-            } else {
-                range[doom as usize - 1]
-            }
-        }
-    }
-}
+/// The number of order-of-magnitude buckets in a [`DoomGram`].
+pub(crate) const NUM_BUCKETS : usize = 12;
+
+/// The inclusive lower bound, in nanoseconds, of each bucket of a
+/// [`DoomGram`]. The upper bound of each bucket is the lower bound of the
+/// next, except for the last, which is unbounded.
+pub(crate) const BUCKET_LOWER_BOUNDS : [u64; NUM_BUCKETS] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+];
 
 
 /// Decimal Order-Of-Magnitude frequency histoGRAM
@@ -93,35 +48,48 @@ pub(crate) mod gram_utils {
 /// This is a Rust port of the equivalent `stlsoft::doomgram` class from the
 /// **STLSoft** libraries:
 /// <https://github.com/synesissoftware/STLSoft-1.11>.
+#[derive(Clone)]
 #[derive(Debug)]
 #[derive(Default)]
+#[derive(Eq, PartialEq)]
 pub struct DoomGram {
-
-    event_count         :   usize,
-
-    event_time_total    :   u64,
-    has_overflowed      :   bool,
-
-    min_event_time      :   Option<u64>,
-    max_event_time      :   Option<u64>,
-
-    num_events_in_1ns   :   u64,
-    num_events_in_10ns  :   u64,
-    num_events_in_100ns :   u64,
-    num_events_in_1us   :   u64,
-    num_events_in_10us  :   u64,
-    num_events_in_100us :   u64,
-    num_events_in_1ms   :   u64,
-    num_events_in_10ms  :   u64,
-    num_events_in_100ms :   u64,
-    num_events_in_1s    :   u64,
-    num_events_in_10s   :   u64,
-    num_events_ge_100s  :   u64,
+    /// The event times, in nanoseconds, with their sum of squares, from
+    /// which the variance is obtained.
+    core : GramCore,
 }
 
 // API functions
 
 impl DoomGram {
+    /// Creates an instance from its constituent parts, as captured by
+    /// another gram implementation (e.g. a snapshot of an
+    /// [`AtomicDoomGram`](super::AtomicDoomGram)).
+    pub(crate) fn from_parts_(
+        event_count : usize,
+        event_time_total : u64,
+        event_time_sum_sq : u128,
+        has_overflowed : bool,
+        min_event_time : Option<u64>,
+        max_event_time : Option<u64>,
+        buckets : [u64; NUM_BUCKETS],
+    ) -> Self {
+        Self {
+            core : GramCore {
+                count : event_count,
+                total : event_time_total,
+                sum_sq : event_time_sum_sq,
+                has_overflowed,
+                min : min_event_time,
+                max : max_event_time,
+                buckets,
+            },
+        }
+    }
+
+    /// The state on which the instance is built.
+    pub(crate) fn core_(&self) -> &GramCore {
+        &self.core
+    }
 }
 
 // Mutating methods
@@ -133,6 +101,41 @@ impl DoomGram {
         *self = Default::default();
     }
 
+    /// Merges the events recorded in `other` into this instance.
+    ///
+    /// Bucket counts, event counts, totals, and sums of squares are summed;
+    /// min and max are combined. The result has overflowed if either
+    /// instance has overflowed, or if summing the totals overflows, in
+    /// which case the total is left as it was before the summation (see
+    /// [`Self::event_time_total_raw()`]).
+    ///
+    /// This is also available via the `+=` and `+` operators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg1 = DoomGram::default();
+    /// let mut dg2 = DoomGram::default();
+    ///
+    /// dg1.push_event_time_ns(7);
+    /// dg1.push_event_time_us(92);
+    /// dg2.push_event_time_ms(3);
+    /// dg2.push_event_time_ns(11);
+    ///
+    /// dg1.merge(&dg2);
+    ///
+    /// assert_eq!("4:7ns-773µs-3ms", dg1.to_nmmm());
+    /// assert_eq!("aa__a_a_____", dg1.to_strip());
+    /// ```
+    pub fn merge(
+        &mut self,
+        other : &DoomGram,
+    ) {
+        self.core.merge(&other.core);
+    }
+
     /// Pushes an event with the given [`Duration`].
     ///
     /// # Note:
@@ -149,16 +152,7 @@ impl DoomGram {
         &mut self,
         time_in_ns : u64,
     ) -> bool {
-        if self.try_add_ns_to_total_and_update_minmax_and_count_(time_in_ns) {
-
-            self.event_count += 1;
-
-            self.push_event_time_ns_(time_in_ns);
-
-            true
-        } else {
-            false
-        }
+        self.core.push(time_in_ns)
     }
 
     /// Pushes an event with the given number of microseconds.
@@ -166,18 +160,7 @@ impl DoomGram {
         &mut self,
         time_in_us : u64,
     ) -> bool {
-        let time_in_ns = 1_000 * time_in_us;
-
-        if self.try_add_ns_to_total_and_update_minmax_and_count_(time_in_ns) {
-
-            self.event_count += 1;
-
-            self.push_event_time_ns_(time_in_ns);
-
-            true
-        } else {
-            false
-        }
+        self.core.push(1_000 * time_in_us)
     }
 
     /// Pushes an event with the given number of milliseconds.
@@ -185,18 +168,7 @@ impl DoomGram {
         &mut self,
         time_in_ms : u64,
     ) -> bool {
-        let time_in_ns = 1_000 * 1_000 * time_in_ms;
-
-        if self.try_add_ns_to_total_and_update_minmax_and_count_(time_in_ns) {
-
-            self.event_count += 1;
-
-            self.push_event_time_ns_(time_in_ns);
-
-            true
-        } else {
-            false
-        }
+        self.core.push(1_000 * 1_000 * time_in_ms)
     }
 
     /// Pushes an event with the given number of seconds.
@@ -204,18 +176,41 @@ impl DoomGram {
         &mut self,
         time_in_s : u64,
     ) -> bool {
-        let time_in_ns = 1_000 * 1_000 * 1_000 * time_in_s;
-
-        if self.try_add_ns_to_total_and_update_minmax_and_count_(time_in_ns) {
-
-            self.event_count += 1;
+        self.core.push(1_000 * 1_000 * 1_000 * time_in_s)
+    }
 
-            self.push_event_time_ns_(time_in_ns);
+    /// Creates a [`DoomGuard`] that records the time elapsed from now
+    /// until it is dropped (or [`DoomGuard::stop()`] is called), unless
+    /// [`DoomGuard::cancel()`] is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// {
+    ///     let _guard = dg.time_scope();
+    ///
+    ///     // ... work to be timed ...
+    /// }
+    ///
+    /// assert_eq!(1, dg.event_count());
+    /// ```
+    pub fn time_scope(&mut self) -> DoomGuard<'_> {
+        DoomGuard::new_(self, InstantClock)
+    }
 
-            true
-        } else {
-            false
-        }
+    /// Creates a [`DoomGuard`] that records the time elapsed, as measured
+    /// by the given [`Clock`], from now until it is dropped (or
+    /// [`DoomGuard::stop()`] is called), unless [`DoomGuard::cancel()`] is
+    /// called.
+    pub fn time_scope_with_clock<C : Clock>(
+        &mut self,
+        clock : C,
+    ) -> DoomGuard<'_, C> {
+        DoomGuard::new_(self, clock)
     }
 }
 
@@ -224,94 +219,180 @@ impl DoomGram {
 impl DoomGram {
     /// Number of events counted
     pub fn event_count(&self) -> usize {
-        self.event_count
+        self.core.count
     }
 
+    /// Obtains the total event time (in nanoseconds), or `None` if
+    /// overflow has occurred.
     pub fn event_time_total(&self) -> Option<u64> {
-        if self.has_overflowed {
+        if self.core.has_overflowed {
             None
         } else {
-            Some(self.event_time_total)
+            Some(self.core.total)
         }
     }
 
     /// Obtains the total event time (in nanoseconds), regardless of whether
-    /// overflow has occurred.
+    /// overflow has occurred, in which case it is the total before the
+    /// push or merge that overflowed.
     pub fn event_time_total_raw(&self) -> u64 {
-        self.event_time_total
+        self.core.total
     }
 
     /// Indicates whether overflow has occurred.
     pub fn has_overflowed(&self) -> bool {
-        self.has_overflowed
+        self.core.has_overflowed
     }
 
     pub fn min_event_time(&self) -> Option<u64> {
-        self.min_event_time
+        self.core.min
     }
 
     pub fn max_event_time(&self) -> Option<u64> {
-        self.max_event_time
+        self.core.max
     }
 
     /// Number of events counted in the interval [1ns, 10ns).
     pub fn num_events_in_1ns(&self) -> u64 {
-        self.num_events_in_1ns
+        self.core.buckets[0]
     }
 
     /// Number of events counted in the interval [10ns, 100ns).
     pub fn num_events_in_10ns(&self) -> u64 {
-        self.num_events_in_10ns
+        self.core.buckets[1]
     }
 
     /// Number of events counted in the interval [100ns, 1µs).
     pub fn num_events_in_100ns(&self) -> u64 {
-        self.num_events_in_100ns
+        self.core.buckets[2]
     }
 
     /// Number of events counted in the interval [1µs, 10µs).
     pub fn num_events_in_1us(&self) -> u64 {
-        self.num_events_in_1us
+        self.core.buckets[3]
     }
 
     /// Number of events counted in the interval [10µs, 100µs).
     pub fn num_events_in_10us(&self) -> u64 {
-        self.num_events_in_10us
+        self.core.buckets[4]
     }
 
     /// Number of events counted in the interval [100µs, 1ms).
     pub fn num_events_in_100us(&self) -> u64 {
-        self.num_events_in_100us
+        self.core.buckets[5]
     }
 
     /// Number of events counted in the interval [1ms, 10ms).
     pub fn num_events_in_1ms(&self) -> u64 {
-        self.num_events_in_1ms
+        self.core.buckets[6]
     }
 
     /// Number of events counted in the interval [10ms, 100ms).
     pub fn num_events_in_10ms(&self) -> u64 {
-        self.num_events_in_10ms
+        self.core.buckets[7]
     }
 
     /// Number of events counted in the interval [100ms, 1s).
     pub fn num_events_in_100ms(&self) -> u64 {
-        self.num_events_in_100ms
+        self.core.buckets[8]
     }
 
     /// Number of events counted in the interval [1s, 10s).
     pub fn num_events_in_1s(&self) -> u64 {
-        self.num_events_in_1s
+        self.core.buckets[9]
     }
 
     /// Number of events counted in the interval [10s, 100s).
     pub fn num_events_in_10s(&self) -> u64 {
-        self.num_events_in_10s
+        self.core.buckets[10]
     }
 
     /// Number of events counted in the interval [100s, ∞).
     pub fn num_events_ge_100s(&self) -> u64 {
-        self.num_events_ge_100s
+        self.core.buckets[11]
+    }
+
+    /// The bucket counts, in order from [`Self::num_events_in_1ns`] to
+    /// [`Self::num_events_ge_100s`].
+    pub(crate) fn buckets_(&self) -> [u64; NUM_BUCKETS] {
+        self.core.buckets
+    }
+
+    /// Obtains the events recorded in this instance that are not recorded
+    /// in `earlier`, which is expected to be a previous capture (e.g. a
+    /// clone) of this same, long-lived, instance.
+    ///
+    /// Bucket counts, event counts, totals, and sums of squares are
+    /// subtracted, saturating at zero. Since the exact min and max of the
+    /// intervening events cannot be recovered, they are estimated as the
+    /// narrowest range that is consistent with both the overall min and
+    /// max of this instance and the bounds of the lowest and highest
+    /// buckets that received events. The result has overflowed if this
+    /// instance has overflowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// dg.push_event_time_ms(13);
+    ///
+    /// let earlier = dg.clone();
+    ///
+    /// dg.push_event_time_us(92);
+    /// dg.push_event_time_us(97);
+    ///
+    /// let delta = dg.saturating_sub(&earlier);
+    ///
+    /// assert_eq!(2, delta.event_count());
+    /// assert_eq!(Some(189_000), delta.event_time_total());
+    /// assert_eq!("____a_______", delta.to_strip());
+    /// ```
+    pub fn saturating_sub(
+        &self,
+        earlier : &DoomGram,
+    ) -> DoomGram {
+        let buckets = self.buckets_();
+        let earlier_buckets = earlier.buckets_();
+
+        let buckets : [u64; NUM_BUCKETS] = std::array::from_fn(|i| buckets[i].saturating_sub(earlier_buckets[i]));
+
+        let event_count = self.core.count.saturating_sub(earlier.core.count);
+
+        let (min_event_time, max_event_time) = if 0 == event_count {
+            (None, None)
+        } else {
+            let num_bucketed = buckets.iter().fold(0u64, |acc, &n| acc.saturating_add(n));
+            let has_zero_time_events = (event_count as u64) > num_bucketed;
+
+            let lowest = if has_zero_time_events {
+                0
+            } else {
+                buckets.iter().position(|&n| 0 != n).map_or(0, |index| BUCKET_LOWER_BOUNDS[index])
+            };
+            let highest = match buckets.iter().rposition(|&n| 0 != n) {
+                Some(index) if index + 1 < NUM_BUCKETS => BUCKET_LOWER_BOUNDS[index + 1] - 1,
+                Some(_) => u64::MAX,
+                None => 0,
+            };
+
+            let min_event_time = self.core.min.map_or(lowest, |min| min.max(lowest));
+            let max_event_time = self.core.max.map_or(highest, |max| max.min(highest));
+
+            (Some(min_event_time.min(max_event_time)), Some(max_event_time))
+        };
+
+        Self::from_parts_(
+            event_count,
+            self.core.total.saturating_sub(earlier.core.total),
+            self.core.sum_sq.saturating_sub(earlier.core.sum_sq),
+            self.core.has_overflowed,
+            min_event_time,
+            max_event_time,
+            buckets,
+        )
     }
 
     /// Returns min, mean, and max event times as a compact duration string.
@@ -325,7 +406,13 @@ impl DoomGram {
     /// Mean is [`Self::event_time_total_raw()`] divided by
     /// [`Self::event_count()`].
     pub fn to_mmm(&self) -> String {
-        self.to_mmm_impl_()
+        self.to_mmm_impl_(false)
+    }
+
+    /// Like [`Self::to_mmm()`], but with microseconds written as `us`
+    /// rather than `µs`, such that the result is ASCII-only.
+    pub fn to_mmm_ascii(&self) -> String {
+        self.to_mmm_impl_(true)
     }
 
     /// Like [`Self::to_mmm()`], prefixed with the event count and `:`.
@@ -334,7 +421,165 @@ impl DoomGram {
     /// [`Self::has_overflowed()`] is true, returns
     /// `"<count>:OVERFLOW"`.
     pub fn to_nmmm(&self) -> String {
-        self.to_nmmm_impl_()
+        self.to_nmmm_impl_(false)
+    }
+
+    /// Like [`Self::to_nmmm()`], but with microseconds written as `us`
+    /// rather than `µs`, such that the result is ASCII-only.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// dg.push_event_time_us(92);
+    /// dg.push_event_time_ms(3);
+    ///
+    /// assert_eq!("2:92µs-1.546ms-3ms", dg.to_nmmm());
+    /// assert_eq!("2:92us-1.546ms-3ms", dg.to_nmmm_ascii());
+    /// ```
+    pub fn to_nmmm_ascii(&self) -> String {
+        self.to_nmmm_impl_(true)
+    }
+
+    /// Like [`Self::to_mmm()`], followed by estimates of the 50th, 90th,
+    /// and 99th percentiles, each obtained from [`Self::quantile()`] and
+    /// formatted by [`crate::nanoseconds_to_string`], as in
+    /// `"7ns-773µs-3ms p50=99ns p90=2.200ms p99=2.920ms"`.
+    ///
+    /// When [`Self::event_count()`] is zero, returns an empty string. When
+    /// [`Self::has_overflowed()`] is true, returns `"OVERFLOW"`.
+    ///
+    /// # Note:
+    /// Each percentile is accurate only to within the decade of the bucket
+    /// in which it lies (see [`DoomGramQuantile`]).
+    pub fn to_pmmm(&self) -> String {
+        use super::time_format::nanoseconds_to_string;
+
+        let mmm = self.to_mmm_impl_(false);
+
+        if 0 == self.event_count() || self.has_overflowed() {
+            return mmm;
+        }
+
+        let mut s = mmm;
+
+        for (label, q) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99)] {
+            // NOTE: cannot fail, since `event_count() != 0` and `q` is valid
+            if let Some(estimate) = self.quantile(q) {
+                s.push_str(&format!(" {label}={}", nanoseconds_to_string(estimate.value() as i64, "")));
+            }
+        }
+
+        s
+    }
+
+    /// Like [`Self::to_mmm()`], followed by the standard deviation, as
+    /// obtained from [`Self::stddev()`] and formatted by
+    /// [`crate::nanoseconds_to_string`], as in `"2ms-4ms-6ms σ=1.632ms"`.
+    ///
+    /// When [`Self::event_count()`] is zero, returns an empty string. When
+    /// [`Self::has_overflowed()`] is true, returns `"OVERFLOW"`.
+    pub fn to_mmms(&self) -> String {
+        use super::time_format::nanoseconds_to_string;
+
+        let mmm = self.to_mmm_impl_(false);
+
+        match self.stddev() {
+            Some(stddev) => format!("{mmm} σ={}", nanoseconds_to_string(stddev.round() as i64, "")),
+            None => mmm,
+        }
+    }
+
+    /// The (population) variance of the recorded event times, in square
+    /// nanoseconds.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no events have been recorded, or if the instance has
+    /// overflowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// for ns in [2, 4, 4, 4, 5, 5, 7, 9] {
+    ///     dg.push_event_time_ns(ns);
+    /// }
+    ///
+    /// assert_eq!(Some(4.0), dg.variance());
+    /// assert_eq!(Some(2.0), dg.stddev());
+    /// ```
+    pub fn variance(&self) -> Option<f64> {
+        if 0 == self.core.count || self.core.has_overflowed {
+            return None;
+        }
+
+        let n = self.core.count as u128;
+        let total = u128::from(self.core.total);
+
+        // n * variance = sum(x^2) - sum(x)^2 / n, which is calculated in
+        // integers (rather than from the mean) to avoid cancellation
+        let deviation_sum_sq = self.core.sum_sq.saturating_sub(total * total / n);
+
+        Some(deviation_sum_sq as f64 / n as f64)
+    }
+
+    /// The (population) standard deviation of the recorded event times, in
+    /// nanoseconds.
+    ///
+    /// # Returns
+    ///
+    /// `None` if no events have been recorded, or if the instance has
+    /// overflowed.
+    pub fn stddev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    /// Estimates the `q`-quantile of the recorded event times (in
+    /// nanoseconds), e.g. `0.99` for the 99th percentile.
+    ///
+    /// The bucket containing the requested rank is located, and the
+    /// estimate is interpolated linearly within that bucket's decade.
+    /// Zero-time events, which are counted but not bucketed, are treated
+    /// as occupying a bucket below the 1ns bucket. The estimate, and its
+    /// bounds, are clamped to [`Self::min_event_time()`] and
+    /// [`Self::max_event_time()`].
+    ///
+    /// # Returns
+    ///
+    /// `None` if no events have been recorded, or if `q` is not in the
+    /// range `[0, 1]`; otherwise the estimate along with the bounds within
+    /// which the true value is known to lie.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DoomGram;
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// for us in 1..=100 {
+    ///     dg.push_event_time_us(us);
+    /// }
+    ///
+    /// let p90 = dg.quantile(0.9).unwrap();
+    ///
+    /// assert_eq!(10_000, p90.lower_bound());
+    /// assert_eq!(99_999, p90.upper_bound());
+    /// assert!(p90.lower_bound() <= p90.value());
+    /// assert!(p90.value() <= p90.upper_bound());
+    /// ```
+    pub fn quantile(
+        &self,
+        q : f64,
+    ) -> Option<DoomGramQuantile> {
+        self.quantile_(q, &self.core.buckets, &BUCKET_LOWER_BOUNDS)
     }
 
     /// Returns a fixed 12-character ASCII strip for the histogram.
@@ -365,229 +610,484 @@ impl DoomGram {
     /// assert_eq!("aa__a_a_____", dg.to_strip());
     /// ```
     pub fn to_strip(&self) -> String {
+        gram_utils::buckets_to_strip(&self.buckets_())
+    }
 
-        // TODO: this may need to be optimised, as costing around 20µs (2µs
-        // release) to process (and it's not helped by a faster
-        // `calc_doom()`):
-        //
-        // - initialise as `[ b'_', b'_', b'_', ... ]` and then don't to call `gram_doom_to_char()` unless count != 0;
-        // - have fields in an array rather than separate fields and then can iterate;
-        // - T.B.D.
-
-        use gram_utils::{
-            calc_doom,
-            gram_doom_to_char,
-        };
-
-        let mut strip : [u8; 12] = Default::default();
-
-        let ch_0 = b'_';
-        let ch_overflow = b'*';
-        let range = b"abcdefghijklmnopqrstuvwxyz";
-
-        strip[0] = gram_doom_to_char(calc_doom(self.num_events_in_1ns()), ch_0, ch_overflow, range);
-        strip[1] = gram_doom_to_char(calc_doom(self.num_events_in_10ns()), ch_0, ch_overflow, range);
-        strip[2] = gram_doom_to_char(calc_doom(self.num_events_in_100ns()), ch_0, ch_overflow, range);
-        strip[3] = gram_doom_to_char(calc_doom(self.num_events_in_1us()), ch_0, ch_overflow, range);
-        strip[4] = gram_doom_to_char(calc_doom(self.num_events_in_10us()), ch_0, ch_overflow, range);
-        strip[5] = gram_doom_to_char(calc_doom(self.num_events_in_100us()), ch_0, ch_overflow, range);
-        strip[6] = gram_doom_to_char(calc_doom(self.num_events_in_1ms()), ch_0, ch_overflow, range);
-        strip[7] = gram_doom_to_char(calc_doom(self.num_events_in_10ms()), ch_0, ch_overflow, range);
-        strip[8] = gram_doom_to_char(calc_doom(self.num_events_in_100ms()), ch_0, ch_overflow, range);
-        strip[9] = gram_doom_to_char(calc_doom(self.num_events_in_1s()), ch_0, ch_overflow, range);
-        strip[10] = gram_doom_to_char(calc_doom(self.num_events_in_10s()), ch_0, ch_overflow, range);
-        strip[11] = gram_doom_to_char(calc_doom(self.num_events_ge_100s()), ch_0, ch_overflow, range);
+    /// Writes the form obtained from [`Self::to_mmm()`] to `w`, without
+    /// allocating.
+    pub fn write_mmm_to<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+    ) -> std_fmt::Result {
+        self.write_mmm_(w, false)
+    }
 
-        // SAFETY: `strip` holds only `_`, `*`, or `a`–`z`. Each byte is
-        // written by `gram_doom_to_char()` as `ch_0`, `ch_overflow`, or an
-        // element of `range` (lowercase ASCII); all are valid UTF-8.
-        let s = unsafe { std_str::from_utf8_unchecked(&strip) };
+    /// Writes the form obtained from [`Self::to_nmmm()`] to `w`, without
+    /// allocating.
+    pub fn write_nmmm_to<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+    ) -> std_fmt::Result {
+        self.write_nmmm_(w, false)
+    }
 
-        s.into()
+    /// Writes the form obtained from [`Self::to_strip()`] to `w`, without
+    /// allocating.
+    pub fn write_strip_to<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+    ) -> std_fmt::Result {
+        gram_utils::write_strip(w, &self.buckets_())
     }
 }
 
 // Implementation
 
 impl DoomGram {
-    fn push_event_time_ns_(
-        &mut self,
-        time_in_ns : u64,
-    ) {
-        if time_in_ns >= 1_000_000 {
-            // >= 1ms
-
-            if time_in_ns >= 1_000_000_000 {
-                // >= 1s
-
-                if time_in_ns >= 100_000_000_000 {
-                    self.num_events_ge_100s += 1;
-                } else if time_in_ns >= 10_000_000_000 {
-                    self.num_events_in_10s += 1;
-                } else {
-                    self.num_events_in_1s += 1;
-                }
-            } else {
-                // < 1s
-
-                if time_in_ns >= 100_000_000 {
-                    self.num_events_in_100ms += 1;
-                } else if time_in_ns >= 10_000_000 {
-                    self.num_events_in_10ms += 1;
-                } else {
-                    self.num_events_in_1ms += 1;
-                }
-            }
-        } else {
-            // < 1ms
-
-            if time_in_ns >= 1_000 {
-                // >= 1µs
-
-                if time_in_ns >= 100_000 {
-                    self.num_events_in_100us += 1;
-                } else if time_in_ns >= 10_000 {
-                    self.num_events_in_10us += 1;
-                } else {
-                    self.num_events_in_1us += 1;
-                }
-            } else {
-                // < 1µs
-
-                if time_in_ns >= 100 {
-                    self.num_events_in_100ns += 1;
-                } else if time_in_ns >= 10 {
-                    self.num_events_in_10ns += 1;
-                } else if time_in_ns >= 1 {
-                    self.num_events_in_1ns += 1;
-                }
-            }
+    /// Estimates the `q`-quantile, as described for [`Self::quantile()`],
+    /// from the given buckets, which partition the non-zero event times of
+    /// the instance, and whose (inclusive) lower bounds are
+    /// `lower_bounds`, the last bucket being unbounded.
+    pub(super) fn quantile_(
+        &self,
+        q : f64,
+        buckets : &[u64],
+        lower_bounds : &[u64],
+    ) -> Option<DoomGramQuantile> {
+        debug_assert_eq!(buckets.len(), lower_bounds.len());
+
+        if 0 == self.core.count || !(0.0..=1.0).contains(&q) {
+            return None;
         }
-    }
 
-    fn to_mmm_impl_(
-        &self,
-    ) -> String {
-        use super::time_format::nanoseconds_to_string;
+        let min = self.core.min?;
+        let max = self.core.max?;
 
-        const OVERFLOW : &str = "OVERFLOW";
+        let num_bucketed = buckets.iter().fold(0u64, |acc, &n| acc.saturating_add(n));
+        let num_zero_time = (self.core.count as u64).saturating_sub(num_bucketed);
 
-        let count = self.event_count();
+        let rank = q * self.core.count as f64;
 
-        if 0 == count {
-                return String::new();
-        }
+        let mut cumulative = 0u64;
 
-        if self.has_overflowed() {
-                return OVERFLOW.into();
-        }
+        // the zero-time events, followed by each bucket, with its bounds
+        let all_buckets = std::iter::once((num_zero_time, 0, 0)).chain(buckets.iter().enumerate().map(|(i, &n)| {
+            // inclusive, as is the lower bound
+            let upper = match lower_bounds.get(i + 1) {
+                Some(&next_lower) => next_lower.saturating_sub(1),
+                None => u64::MAX,
+            };
 
-        let min_ns = self.min_event_time().unwrap() as i64;
-        let max_ns = self.max_event_time().unwrap() as i64;
+            (n, lower_bounds[i], upper)
+        }));
 
-        let body = if 1 == count || min_ns == max_ns {
-            format!("{}", nanoseconds_to_string(min_ns, ""))
-        } else {
-            let mean_ns = (self.event_time_total_raw() / count as u64) as i64;
-
-            format!(
-                "{}-{}-{}",
-                nanoseconds_to_string(min_ns, ""),
-                nanoseconds_to_string(mean_ns, ""),
-                nanoseconds_to_string(max_ns, ""),
-            )
-        };
+        for (count, lower, upper) in all_buckets {
+            if 0 == count {
+                continue;
+            }
 
-        body
-    }
+            let next_cumulative = cumulative.saturating_add(count);
 
-    fn to_nmmm_impl_(
-        &self,
-    ) -> String {
-        use super::time_format::nanoseconds_to_string;
+            if rank <= next_cumulative as f64 || next_cumulative as usize >= self.core.count {
+                let lower = lower.clamp(min, max);
+                let upper = upper.clamp(min, max);
 
-        const OVERFLOW : &str = "OVERFLOW";
+                let fraction = ((rank - cumulative as f64) / count as f64).clamp(0.0, 1.0);
 
-        let count = self.event_count();
+                let value = lower + (fraction * (upper - lower) as f64) as u64;
 
-        if 0 == count {
-                return "0:".into();
-        }
+                return Some(DoomGramQuantile {
+                    value : value.clamp(lower, upper),
+                    lower_bound : lower,
+                    upper_bound : upper,
+                });
+            }
 
-        if self.has_overflowed() {
-                return format!("{count}:{OVERFLOW}");
+            cumulative = next_cumulative;
         }
 
-        let min_ns = self.min_event_time().unwrap() as i64;
-        let max_ns = self.max_event_time().unwrap() as i64;
+        None
+    }
 
-        let body = if 1 == count || min_ns == max_ns {
-            format!("{count}:{}", nanoseconds_to_string(min_ns, ""))
-        } else {
-            let mean_ns = (self.event_time_total_raw() / count as u64) as i64;
-
-            format!(
-                "{count}:{}-{}-{}",
-                nanoseconds_to_string(min_ns, ""),
-                nanoseconds_to_string(mean_ns, ""),
-                nanoseconds_to_string(max_ns, ""),
-            )
+    fn write_padded_strip_to_(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+        width : usize,
+    ) -> std_fmt::Result {
+        use std_fmt::Write as _;
+
+        let num_padding = width.saturating_sub(NUM_BUCKETS);
+        let (num_before, num_after) = match f.align() {
+            Some(std_fmt::Alignment::Right) => (num_padding, 0),
+            Some(std_fmt::Alignment::Center) => (num_padding / 2, num_padding - num_padding / 2),
+            Some(std_fmt::Alignment::Left) | None => (0, num_padding),
         };
+        let fill = f.fill();
 
-        body
-    }
+        for _ in 0..num_before {
+            f.write_char(fill)?;
+        }
 
-    fn try_add_ns_to_total_and_update_minmax_and_count_(
-        &mut self,
-        time_in_ns : u64,
-    ) -> bool {
+        self.write_strip_to(f)?;
 
-        if self.has_overflowed {
-            return false;
+        for _ in 0..num_after {
+            f.write_char(fill)?;
         }
 
-        match self.event_time_total.checked_add(time_in_ns) {
-            Some(new_total) => {
-
-                self.event_time_total = new_total;
+        Ok(())
+    }
 
-                match self.min_event_time {
-                    Some(min_event_time) => {
-                        if time_in_ns < min_event_time {
-                            self.min_event_time = Some(time_in_ns);
-                        }
-                    },
-                    None => {
-                        self.min_event_time = Some(time_in_ns);
-                    },
-                };
-
-                match self.max_event_time {
-                    Some(max_event_time) => {
-                        if time_in_ns > max_event_time {
-                            self.max_event_time = Some(time_in_ns);
-                        }
-                    },
-                    None => {
-                        self.max_event_time = Some(time_in_ns);
-                    },
-                };
+    fn write_mmm_<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+        ascii : bool,
+    ) -> std_fmt::Result {
+        let df = DurationFormat::new().ascii(ascii);
 
-                true
-            },
-            None => {
+        self.core.write_mmm(w, |w, time_in_ns| w.write_str(&df.format(time_in_ns as i64)))
+    }
 
-                self.has_overflowed = true;
+    fn write_nmmm_<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+        ascii : bool,
+    ) -> std_fmt::Result {
+        write!(w, "{}:", self.event_count())?;
 
-                false
-            },
-        }
+        self.write_mmm_(w, ascii)
     }
-}
 
-// Trait implementations
+    fn to_mmm_impl_(
+        &self,
+        ascii : bool,
+    ) -> String {
+        let mut s = String::new();
+
+        // NOTE: writing to a `String` cannot fail
+        let _ = self.write_mmm_(&mut s, ascii);
+
+        s
+    }
+
+    fn to_nmmm_impl_(
+        &self,
+        ascii : bool,
+    ) -> String {
+        let mut s = String::new();
+
+        // NOTE: writing to a `String` cannot fail
+        let _ = self.write_nmmm_(&mut s, ascii);
+
+        s
+    }
+}
+
+// Trait implementations
+
+impl std_fmt::Display for DoomGram {
+    /// Writes the form obtained from [`DoomGram::to_nmmm()`], as in
+    /// `"4:7ns-773µs-3ms"`, and modified by the format flags as follows:
+    ///
+    /// * alternate (`{:#}`) - the nmmm form is followed by a space and the
+    ///   strip, as in `"4:7ns-773µs-3ms aa__a_a_____"`;
+    /// * width (`{:20}`) - the strip, padded to the given width with the
+    ///   given fill and alignment (left, by default), is written instead
+    ///   of the nmmm form; or, if also alternate (`{:#20}`), after the
+    ///   nmmm form and a space;
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        match f.width() {
+            None => {
+                self.write_nmmm_to(f)?;
+
+                if f.alternate() {
+                    f.write_str(" ")?;
+
+                    self.write_strip_to(f)?;
+                }
+
+                Ok(())
+            },
+            Some(width) => {
+                if f.alternate() {
+                    self.write_nmmm_to(f)?;
+
+                    f.write_str(" ")?;
+                }
+
+                self.write_padded_strip_to_(f, width)
+            },
+        }
+    }
+}
+
 
-// NONE DEFINED AT THIS TIME
+impl std_ops::Add for DoomGram {
+    type Output = Self;
+
+    fn add(
+        mut self,
+        rhs : Self,
+    ) -> Self::Output {
+        self.merge(&rhs);
+
+        self
+    }
+}
+
+
+impl std_ops::Add<&DoomGram> for DoomGram {
+    type Output = Self;
+
+    fn add(
+        mut self,
+        rhs : &DoomGram,
+    ) -> Self::Output {
+        self.merge(rhs);
+
+        self
+    }
+}
+
+
+impl std_ops::AddAssign for DoomGram {
+    fn add_assign(
+        &mut self,
+        rhs : Self,
+    ) {
+        self.merge(&rhs);
+    }
+}
+
+
+impl std_ops::AddAssign<&DoomGram> for DoomGram {
+    fn add_assign(
+        &mut self,
+        rhs : &DoomGram,
+    ) {
+        self.merge(rhs);
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DoomGram {
+    fn serialize<S>(
+        &self,
+        serializer : S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S : serde::Serializer,
+    {
+        serde_repr_::DoomGramRepr::from(self).serialize(serializer)
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DoomGram {
+    fn deserialize<D>(deserializer : D) -> Result<Self, D::Error>
+    where
+        D : serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+
+        let repr = serde_repr_::DoomGramRepr::deserialize(deserializer)?;
+
+        DoomGram::try_from(repr).map_err(D::Error::custom)
+    }
+}
+
+
+#[cfg(feature = "serde")]
+mod serde_repr_ {
+    use super::DoomGram;
+
+
+    /// The serialized form of a [`DoomGram`]. The field names (and their
+    /// order) are part of the crate's stable interface, and must not be
+    /// changed; new fields are appended, and must be optional.
+    #[derive(serde::Deserialize)]
+    #[derive(serde::Serialize)]
+    pub(super) struct DoomGramRepr {
+        event_count :         usize,
+        event_time_total :    u64,
+        has_overflowed :      bool,
+        min_event_time :      Option<u64>,
+        max_event_time :      Option<u64>,
+        num_events_in_1ns :   u64,
+        num_events_in_10ns :  u64,
+        num_events_in_100ns : u64,
+        num_events_in_1us :   u64,
+        num_events_in_10us :  u64,
+        num_events_in_100us : u64,
+        num_events_in_1ms :   u64,
+        num_events_in_10ms :  u64,
+        num_events_in_100ms : u64,
+        num_events_in_1s :    u64,
+        num_events_in_10s :   u64,
+        num_events_ge_100s :  u64,
+        /// Absent from data serialized by versions prior to 0.5.
+        #[serde(default)]
+        event_time_sum_sq :   Option<u128>,
+    }
+
+    impl From<&DoomGram> for DoomGramRepr {
+        fn from(dg : &DoomGram) -> Self {
+            Self {
+                event_count : dg.event_count(),
+                event_time_total : dg.event_time_total_raw(),
+                has_overflowed : dg.has_overflowed(),
+                min_event_time : dg.min_event_time(),
+                max_event_time : dg.max_event_time(),
+                num_events_in_1ns : dg.num_events_in_1ns(),
+                num_events_in_10ns : dg.num_events_in_10ns(),
+                num_events_in_100ns : dg.num_events_in_100ns(),
+                num_events_in_1us : dg.num_events_in_1us(),
+                num_events_in_10us : dg.num_events_in_10us(),
+                num_events_in_100us : dg.num_events_in_100us(),
+                num_events_in_1ms : dg.num_events_in_1ms(),
+                num_events_in_10ms : dg.num_events_in_10ms(),
+                num_events_in_100ms : dg.num_events_in_100ms(),
+                num_events_in_1s : dg.num_events_in_1s(),
+                num_events_in_10s : dg.num_events_in_10s(),
+                num_events_ge_100s : dg.num_events_ge_100s(),
+                event_time_sum_sq : Some(dg.core.sum_sq),
+            }
+        }
+    }
+
+    impl TryFrom<DoomGramRepr> for DoomGram {
+        type Error = &'static str;
+
+        fn try_from(repr : DoomGramRepr) -> Result<Self, Self::Error> {
+            // the least sum of squares consistent with the count and total
+            let min_sum_sq = if 0 == repr.event_count {
+                0
+            } else {
+                u128::from(repr.event_time_total) * u128::from(repr.event_time_total) / repr.event_count as u128
+            };
+
+            // data that lack the sum of squares are given the least value,
+            // for which the variance is (approximately) zero
+            let event_time_sum_sq = repr.event_time_sum_sq.unwrap_or(min_sum_sq);
+
+            let dg = DoomGram::from_parts_(
+                repr.event_count,
+                repr.event_time_total,
+                event_time_sum_sq,
+                repr.has_overflowed,
+                repr.min_event_time,
+                repr.max_event_time,
+                [
+                    repr.num_events_in_1ns,
+                    repr.num_events_in_10ns,
+                    repr.num_events_in_100ns,
+                    repr.num_events_in_1us,
+                    repr.num_events_in_10us,
+                    repr.num_events_in_100us,
+                    repr.num_events_in_1ms,
+                    repr.num_events_in_10ms,
+                    repr.num_events_in_100ms,
+                    repr.num_events_in_1s,
+                    repr.num_events_in_10s,
+                    repr.num_events_ge_100s,
+                ],
+            );
+
+            let bucket_sum = dg
+                .buckets_()
+                .iter()
+                .try_fold(0u64, |sum, &n| sum.checked_add(n))
+                .ok_or("bucket counts overflow")?;
+            let event_count = dg.event_count() as u64;
+
+            // zero-time events are counted but not bucketed, so a shortfall
+            // is permitted only when the minimum is zero
+            if bucket_sum > event_count {
+                return Err("bucket counts exceed event_count");
+            }
+            if bucket_sum < event_count && Some(0) != dg.min_event_time() {
+                return Err("bucket counts do not sum to event_count");
+            }
+
+            match (dg.min_event_time(), dg.max_event_time()) {
+                (None, None) => {
+                    if 0 != dg.event_count() {
+                        return Err("missing min_event_time / max_event_time");
+                    }
+                    if 0 != dg.core.sum_sq {
+                        return Err("unexpected event_time_sum_sq");
+                    }
+                },
+                (Some(min), Some(max)) => {
+                    if 0 == dg.event_count() {
+                        return Err("unexpected min_event_time / max_event_time");
+                    }
+                    if min > max {
+                        return Err("min_event_time exceeds max_event_time");
+                    }
+                    if !dg.has_overflowed() {
+                        if dg.core.sum_sq < min_sum_sq {
+                            return Err("event_time_sum_sq too small for event_time_total");
+                        }
+                        if dg.core.sum_sq > u128::from(dg.event_time_total_raw()) * u128::from(max) {
+                            return Err("event_time_sum_sq too large for max_event_time");
+                        }
+                    }
+                },
+                _ => {
+                    return Err("only one of min_event_time / max_event_time");
+                },
+            }
+
+            Ok(dg)
+        }
+    }
+}
+
+
+/// An estimate of a quantile of the event times recorded in a
+/// [`DoomGram`], as obtained from [`DoomGram::quantile()`] (or
+/// [`FineDoomGram::quantile()`](crate::FineDoomGram::quantile)).
+///
+/// Because a [`DoomGram`] records only the decade into which each event
+/// falls, the estimate is accurate only to within one order of magnitude
+/// (or, for a `FineDoomGram`, one sub-bucket): the true value is known to
+/// lie within the inclusive range
+/// [`Self::lower_bound()`]..=[`Self::upper_bound()`], which are the lowest
+/// and highest values of the containing bucket (e.g. 10000 and 99999 for
+/// the 10µs bucket) clamped to the observed min and max.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub struct DoomGramQuantile {
+    value :       u64,
+    lower_bound : u64,
+    upper_bound : u64,
+}
+
+// Non-mutating methods
+
+impl DoomGramQuantile {
+    /// The estimated value, in nanoseconds.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The lowest value, in nanoseconds, that the quantile may have.
+    pub fn lower_bound(&self) -> u64 {
+        self.lower_bound
+    }
+
+    /// The highest value, in nanoseconds, that the quantile may have.
+    pub fn upper_bound(&self) -> u64 {
+        self.upper_bound
+    }
+}
 
 
 /// Executes a closure, records its elapsed time in a [`DoomGram`], and
@@ -603,13 +1103,54 @@ pub fn doom_scope<F, R>(
 where
     F : FnOnce() -> R,
 {
-    let before = Instant::now();
+    doom_scope_with_clock(dg, InstantClock, work)
+}
+
+/// Executes a closure, records its elapsed time, as measured by the given
+/// [`Clock`], in a [`DoomGram`], and returns the closure's result together
+/// with the measured elapsed time (in nanoseconds).
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     doom_scope_with_clock,
+///     DoomGram,
+///     ManualClock,
+/// };
+///
+/// let clock = ManualClock::new(0);
+/// let mut dg = DoomGram::default();
+///
+/// let (r, elapsed_ns) = doom_scope_with_clock(&mut dg, &clock, || {
+///     clock.advance_ns(250_000_000);
+///
+///     "done"
+/// });
+///
+/// assert_eq!("done", r);
+/// assert_eq!(250_000_000, elapsed_ns);
+/// assert_eq!("1:250ms", dg.to_nmmm());
+/// ```
+pub fn doom_scope_with_clock<C, F, R>(
+    dg : &mut DoomGram,
+    clock : C,
+    work : F,
+) -> (
+    R,   // work_result
+    u64, // measured_elapsed_time_in_ns
+)
+where
+    C : Clock,
+    F : FnOnce() -> R,
+{
+    let before = clock.now_ns();
 
     let work_result = work();
 
-    let after = Instant::now();
+    let after = clock.now_ns();
 
-    let measured_elapsed_time_in_ns = (after - before).as_nanos() as u64;
+    let measured_elapsed_time_in_ns = after.saturating_sub(before);
 
     dg.push_event_time_ns(measured_elapsed_time_in_ns);
 
@@ -627,8 +1168,11 @@ mod tests {
     use super::{
         DoomGram,
         doom_scope,
+        doom_scope_with_clock,
     };
 
+    use crate::diagnostics::ManualClock;
+
     use std::{
         thread as std_thread,
         time::Duration,
@@ -1176,6 +1720,538 @@ mod tests {
     }
 
 
+    #[test]
+    fn TEST_DoomGram_merge_EMPTY() {
+        let mut dg = DoomGram::default();
+
+        dg.merge(&DoomGram::default());
+
+        assert_eq!(DoomGram::default(), dg);
+
+        let mut other = DoomGram::default();
+
+        other.push_event_time_ms(13);
+
+        dg.merge(&other);
+
+        assert_eq!(other, dg);
+    }
+
+    #[test]
+    fn TEST_DoomGram_merge_DISJOINT() {
+        let mut dg1 = DoomGram::default();
+        let mut dg2 = DoomGram::default();
+        let mut expected = DoomGram::default();
+
+        for ns in [23, 10, 7_000, 7_000, 89_000] {
+            dg1.push_event_time_ns(ns);
+            expected.push_event_time_ns(ns);
+        }
+        for ns in [0, 248_000_000, 5_000_000_000, 309_000_000_000] {
+            dg2.push_event_time_ns(ns);
+            expected.push_event_time_ns(ns);
+        }
+
+        dg1.merge(&dg2);
+
+        assert_eq!(expected, dg1);
+
+        assert_eq!("9:0s-34.91s-309s", dg1.to_nmmm());
+        assert_eq!("_a_aa___aa_a", dg1.to_strip());
+    }
+
+    #[test]
+    fn TEST_DoomGram_Add_AND_AddAssign() {
+        let mut dg1 = DoomGram::default();
+        let mut dg2 = DoomGram::default();
+
+        dg1.push_event_time_s(1);
+        dg2.push_event_time_s(2);
+
+        let dg3 = dg1.clone() + &dg2;
+
+        assert_eq!("2:1s-1.500s-2s", dg3.to_nmmm());
+
+        let dg4 = dg1.clone() + dg2.clone();
+
+        assert_eq!(dg3, dg4);
+
+        let mut dg5 = DoomGram::default();
+
+        dg5 += &dg1;
+        dg5 += dg2;
+
+        assert_eq!(dg3, dg5);
+    }
+
+    #[test]
+    fn TEST_DoomGram_merge_OVERFLOWED() {
+        let mut dg1 = DoomGram::default();
+        let mut dg2 = DoomGram::default();
+
+        dg1.push_event_time_s(18_446_744_073);
+        dg2.push_event_time_s(1);
+
+        assert!(!dg1.has_overflowed());
+        assert!(!dg2.has_overflowed());
+
+        // overflow via summation of the totals
+        {
+            let dg = dg1.clone() + &dg2;
+
+            assert!(dg.has_overflowed());
+            assert_eq!(2, dg.event_count());
+            assert_eq!(None, dg.event_time_total());
+            assert_eq!(18_446_744_073_000_000_000, dg.event_time_total_raw());
+            assert_eq!("2:OVERFLOW", dg.to_nmmm());
+        }
+
+        // overflow passed on from either operand
+        {
+            let mut overflowed = dg1.clone();
+
+            assert!(!overflowed.push_event_time_s(1));
+            assert!(overflowed.has_overflowed());
+
+            assert!((DoomGram::default() + &overflowed).has_overflowed());
+            assert!((overflowed + &DoomGram::default()).has_overflowed());
+        }
+    }
+
+    #[test]
+    fn TEST_DoomGram_saturating_sub_OF_EARLIER_CAPTURE() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(7);
+        dg.push_event_time_ms(13);
+
+        let earlier = dg.clone();
+
+        dg.push_event_time_us(92);
+        dg.push_event_time_us(97);
+        dg.push_event_time_us(312);
+
+        let delta = dg.saturating_sub(&earlier);
+
+        assert_eq!(3, delta.event_count());
+        assert_eq!(Some(501_000), delta.event_time_total());
+        assert!(!delta.has_overflowed());
+
+        assert_eq!(0, delta.num_events_in_1ns());
+        assert_eq!(2, delta.num_events_in_10us());
+        assert_eq!(1, delta.num_events_in_100us());
+        assert_eq!(0, delta.num_events_in_10ms());
+
+        // estimated from the bounds of the buckets
+        assert_eq!(Some(10_000), delta.min_event_time());
+        assert_eq!(Some(999_999), delta.max_event_time());
+
+        assert_eq!("____aa______", delta.to_strip());
+
+        // the earlier capture plus the delta gives the full instance, less
+        // the (unrecoverable) exact min and max
+        let rebuilt = earlier + &delta;
+
+        assert_eq!(dg.event_count(), rebuilt.event_count());
+        assert_eq!(dg.event_time_total(), rebuilt.event_time_total());
+        assert_eq!(dg.to_strip(), rebuilt.to_strip());
+    }
+
+    #[test]
+    fn TEST_DoomGram_saturating_sub_CLAMPS_TO_OVERALL_MINMAX() {
+        let mut dg = DoomGram::default();
+
+        let earlier = dg.clone();
+
+        dg.push_event_time_us(92);
+        dg.push_event_time_us(97);
+
+        let delta = dg.saturating_sub(&earlier);
+
+        assert_eq!(Some(92_000), delta.min_event_time());
+        assert_eq!(Some(97_000), delta.max_event_time());
+        assert_eq!("2:92µs-94.50µs-97µs", delta.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGram_saturating_sub_NO_NEW_EVENTS() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ms(13);
+
+        let delta = dg.saturating_sub(&dg.clone());
+
+        assert_eq!(DoomGram::default(), delta);
+
+        // saturates when the operands are reversed
+        let delta = DoomGram::default().saturating_sub(&dg);
+
+        assert_eq!(DoomGram::default(), delta);
+    }
+
+    #[test]
+    fn TEST_DoomGram_saturating_sub_ZERO_TIME_EVENTS() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ms(13);
+
+        let earlier = dg.clone();
+
+        dg.push_event_time_ns(0);
+        dg.push_event_time_ns(0);
+
+        let delta = dg.saturating_sub(&earlier);
+
+        assert_eq!(2, delta.event_count());
+        assert_eq!(Some(0), delta.min_event_time());
+        assert_eq!(Some(0), delta.max_event_time());
+        assert_eq!("2:0s", delta.to_nmmm());
+    }
+
+
+    #[test]
+    fn TEST_DoomGram_quantile_EMPTY() {
+        let dg = DoomGram::default();
+
+        assert_eq!(None, dg.quantile(0.5));
+        assert_eq!("", dg.to_pmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_INVALID_q() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ms(13);
+
+        assert_eq!(None, dg.quantile(-0.01));
+        assert_eq!(None, dg.quantile(1.01));
+        assert_eq!(None, dg.quantile(f64::NAN));
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_SINGLE() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ms(13);
+
+        for q in [0.0, 0.5, 0.99, 1.0] {
+            let estimate = dg.quantile(q).unwrap();
+
+            assert_eq!(13_000_000, estimate.value());
+            assert_eq!(13_000_000, estimate.lower_bound());
+            assert_eq!(13_000_000, estimate.upper_bound());
+        }
+
+        assert_eq!("13ms p50=13ms p90=13ms p99=13ms", dg.to_pmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_EXTREMES_ARE_MIN_AND_MAX() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(7);
+        dg.push_event_time_ms(3);
+        dg.push_event_time_us(92);
+        dg.push_event_time_ns(11);
+
+        assert_eq!(7, dg.quantile(0.0).unwrap().value());
+        assert_eq!(3_000_000, dg.quantile(1.0).unwrap().value());
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_INTERPOLATES_WITHIN_DECADE() {
+        let mut dg = DoomGram::default();
+
+        for us in 1..=100 {
+            dg.push_event_time_us(us);
+        }
+
+        {
+            let p50 = dg.quantile(0.5).unwrap();
+
+            assert_eq!(10_000, p50.lower_bound());
+            assert_eq!(99_999, p50.upper_bound());
+            assert_eq!(50_999, p50.value());
+        }
+
+        {
+            let p90 = dg.quantile(0.9).unwrap();
+
+            assert_eq!(10_000, p90.lower_bound());
+            assert_eq!(99_999, p90.upper_bound());
+            assert_eq!(90_999, p90.value());
+        }
+
+        {
+            let p05 = dg.quantile(0.05).unwrap();
+
+            assert_eq!(1_000, p05.lower_bound());
+            assert_eq!(9_999, p05.upper_bound());
+            assert_eq!(5_999, p05.value());
+        }
+
+        {
+            let p100 = dg.quantile(1.0).unwrap();
+
+            assert_eq!(100_000, p100.lower_bound());
+            assert_eq!(100_000, p100.upper_bound());
+            assert_eq!(100_000, p100.value());
+        }
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_UPPER_BOUND_IS_INCLUSIVE() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(10);
+        dg.push_event_time_us(1);
+
+        let p50 = dg.quantile(0.5).unwrap();
+
+        assert_eq!(10, p50.lower_bound());
+        assert_eq!(99, p50.upper_bound());
+        assert_eq!(99, p50.value());
+
+        // the bounds lie within the same bucket
+        let mut lower = DoomGram::default();
+        let mut upper = DoomGram::default();
+
+        lower.push_event_time_ns(p50.lower_bound());
+        upper.push_event_time_ns(p50.upper_bound());
+
+        assert_eq!(lower.to_strip(), upper.to_strip());
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_ZERO_TIME_EVENTS() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(0);
+        dg.push_event_time_ns(0);
+        dg.push_event_time_ns(0);
+        dg.push_event_time_us(50);
+
+        let p50 = dg.quantile(0.5).unwrap();
+
+        assert_eq!(0, p50.value());
+        assert_eq!(0, p50.lower_bound());
+        assert_eq!(0, p50.upper_bound());
+
+        let p99 = dg.quantile(0.99).unwrap();
+
+        assert_eq!(10_000, p99.lower_bound());
+        assert_eq!(50_000, p99.upper_bound());
+    }
+
+    #[test]
+    fn TEST_DoomGram_quantile_GE_100S_BUCKET() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_s(309);
+        dg.push_event_time_s(5_000);
+
+        let p99 = dg.quantile(0.99).unwrap();
+
+        assert_eq!(309_000_000_000, p99.lower_bound());
+        assert_eq!(5_000_000_000_000, p99.upper_bound());
+    }
+
+    #[test]
+    fn TEST_DoomGram_to_pmmm() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(7);
+        dg.push_event_time_ms(3);
+        dg.push_event_time_us(92);
+        dg.push_event_time_ns(11);
+
+        assert_eq!("7ns-773µs-3ms p50=99ns p90=2.200ms p99=2.920ms", dg.to_pmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGram_to_pmmm_OVERFLOW() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_s(18_446_744_073);
+        dg.push_event_time_s(1);
+
+        assert_eq!("OVERFLOW", dg.to_pmmm());
+    }
+
+    #[test]
+    fn TEST_DoomGram_variance_AND_stddev() {
+        let mut dg = DoomGram::default();
+
+        assert_eq!(None, dg.variance());
+        assert_eq!(None, dg.stddev());
+
+        dg.push_event_time_ms(5);
+
+        assert_eq!(Some(0.0), dg.variance());
+        assert_eq!(Some(0.0), dg.stddev());
+
+        dg.push_event_time_ms(1);
+        dg.push_event_time_ms(3);
+
+        // deviations of -2ms, 0, and +2ms
+        assert_eq!(Some(8e12 / 3.0), dg.variance());
+        assert_eq!(1_632_993, dg.stddev().unwrap().round() as u64);
+    }
+
+    #[test]
+    fn TEST_DoomGram_variance_LARGE_VALUES() {
+        let mut dg = DoomGram::default();
+
+        // a large mean, relative to which the deviation is tiny
+        for ns in [1_000_000_000_001, 1_000_000_000_003] {
+            dg.push_event_time_ns(ns);
+        }
+
+        assert_eq!(Some(1.0), dg.variance());
+        assert_eq!(Some(1.0), dg.stddev());
+
+        for _ in 0..4 {
+            dg.push_event_time_s(4_000_000_000);
+        }
+
+        assert!(!dg.has_overflowed());
+        assert!(dg.stddev().unwrap() > 1.8e18);
+    }
+
+    #[test]
+    fn TEST_DoomGram_variance_OVERFLOW() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_s(18_446_744_073);
+
+        assert_eq!(Some(0.0), dg.variance());
+
+        assert!(!dg.push_event_time_s(1));
+
+        assert_eq!(None, dg.variance());
+        assert_eq!(None, dg.stddev());
+    }
+
+    #[test]
+    fn TEST_DoomGram_variance_merge_AND_saturating_sub() {
+        let mut dg1 = DoomGram::default();
+        let mut dg2 = DoomGram::default();
+        let mut all = DoomGram::default();
+
+        for us in [1, 3, 5] {
+            dg1.push_event_time_us(us);
+            all.push_event_time_us(us);
+        }
+        for us in [200, 400] {
+            dg2.push_event_time_us(us);
+            all.push_event_time_us(us);
+        }
+
+        let merged = dg1.clone() + &dg2;
+
+        assert_eq!(all.variance(), merged.variance());
+        assert_eq!(dg2.variance(), merged.saturating_sub(&dg1).variance());
+    }
+
+    #[test]
+    fn TEST_DoomGram_to_mmms() {
+        let mut dg = DoomGram::default();
+
+        assert_eq!("", dg.to_mmms());
+
+        dg.push_event_time_ms(4);
+
+        assert_eq!("4ms σ=0s", dg.to_mmms());
+
+        dg.push_event_time_ms(2);
+        dg.push_event_time_ms(6);
+
+        assert_eq!("2ms-4ms-6ms σ=1.632ms", dg.to_mmms());
+
+        dg.push_event_time_s(18_446_744_073);
+        dg.push_event_time_s(1);
+
+        assert_eq!("OVERFLOW", dg.to_mmms());
+    }
+
+    #[test]
+    fn TEST_DoomGram_Display() {
+        let mut dg = DoomGram::default();
+
+        assert_eq!("0:", format!("{dg}"));
+        assert_eq!("0: ____________", format!("{dg:#}"));
+
+        dg.push_event_time_ns(7);
+        dg.push_event_time_ms(3);
+        dg.push_event_time_us(92);
+        dg.push_event_time_ns(11);
+
+        assert_eq!("4:7ns-773µs-3ms", format!("{dg}"));
+        assert_eq!("4:7ns-773µs-3ms aa__a_a_____", format!("{dg:#}"));
+        assert_eq!(dg.to_nmmm(), dg.to_string());
+
+        assert_eq!("aa__a_a_____", format!("{dg:1}"));
+        assert_eq!("aa__a_a_____    ", format!("{dg:16}"));
+        assert_eq!("    aa__a_a_____", format!("{dg:>16}"));
+        assert_eq!("..aa__a_a_____...", format!("{dg:.^17}"));
+        assert_eq!("4:7ns-773µs-3ms aa__a_a_____  ", format!("{dg:#14}"));
+    }
+
+    #[test]
+    fn TEST_DoomGram_write_TO() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_us(1);
+        dg.push_event_time_us(3);
+
+        let mut s = String::new();
+
+        dg.write_mmm_to(&mut s).unwrap();
+        s.push('|');
+        dg.write_nmmm_to(&mut s).unwrap();
+        s.push('|');
+        dg.write_strip_to(&mut s).unwrap();
+
+        assert_eq!("1µs-2µs-3µs|2:1µs-2µs-3µs|___a________", s);
+
+        dg.push_event_time_s(18_446_744_073);
+        dg.push_event_time_s(1);
+
+        let mut s = String::new();
+
+        dg.write_nmmm_to(&mut s).unwrap();
+
+        assert_eq!(dg.to_nmmm(), s);
+    }
+
+    #[test]
+    fn TEST_DoomGram_to_mmm_ascii() {
+        let mut dg = DoomGram::default();
+
+        assert_eq!("", dg.to_mmm_ascii());
+        assert_eq!("0:", dg.to_nmmm_ascii());
+
+        dg.push_event_time_us(7);
+
+        assert_eq!("7us", dg.to_mmm_ascii());
+        assert_eq!("1:7us", dg.to_nmmm_ascii());
+
+        dg.push_event_time_ns(8_123);
+        dg.push_event_time_ms(2);
+
+        assert_eq!("7us-671.7us-2ms", dg.to_mmm_ascii());
+        assert_eq!("3:7us-671.7us-2ms", dg.to_nmmm_ascii());
+        assert_eq!(dg.to_nmmm().replace('µ', "u"), dg.to_nmmm_ascii());
+        assert!(dg.to_nmmm_ascii().is_ascii());
+
+        dg.push_event_time_s(18_446_744_073);
+        dg.push_event_time_s(1);
+
+        assert_eq!("OVERFLOW", dg.to_mmm_ascii());
+        assert_eq!("4:OVERFLOW", dg.to_nmmm_ascii());
+    }
+
+
     #[test]
     fn TEST_doom_scope_1() {
         {
@@ -1202,4 +2278,160 @@ mod tests {
             assert!(t >= 1_000);
         }
     }
+
+    #[test]
+    fn TEST_doom_scope_with_clock_ManualClock() {
+        let clock = ManualClock::new(5);
+        let mut dg = DoomGram::default();
+
+        for time_in_ns in [7, 700, 70_000] {
+            let (_, t) = doom_scope_with_clock(&mut dg, &clock, || clock.advance_ns(time_in_ns));
+
+            assert_eq!(time_in_ns, t);
+        }
+
+        let (_, t) = doom_scope_with_clock(&mut dg, &clock, || {});
+
+        assert_eq!(0, t);
+        assert_eq!("4:0s-17.67µs-70µs", dg.to_nmmm());
+        assert_eq!("a_a_a_______", dg.to_strip());
+    }
+
+    #[cfg(feature = "serde")]
+    mod TEST_serde {
+        #![allow(non_snake_case)]
+
+        use super::DoomGram;
+
+
+        #[test]
+        fn TEST_DoomGram_STABLE_FIELD_LAYOUT() {
+            let mut dg = DoomGram::default();
+
+            dg.push_event_time_ns(0);
+            dg.push_event_time_ns(7);
+            dg.push_event_time_ms(3);
+
+            let expected = concat!(
+                "{",
+                r#""event_count":3,"event_time_total":3000007,"has_overflowed":false,"#,
+                r#""min_event_time":0,"max_event_time":3000000,"#,
+                r#""num_events_in_1ns":1,"num_events_in_10ns":0,"num_events_in_100ns":0,"#,
+                r#""num_events_in_1us":0,"num_events_in_10us":0,"num_events_in_100us":0,"#,
+                r#""num_events_in_1ms":1,"num_events_in_10ms":0,"num_events_in_100ms":0,"#,
+                r#""num_events_in_1s":0,"num_events_in_10s":0,"num_events_ge_100s":0,"#,
+                r#""event_time_sum_sq":9000000000049"#,
+                "}",
+            );
+
+            assert_eq!(expected, serde_json::to_string(&dg).unwrap());
+        }
+
+        #[test]
+        fn TEST_DoomGram_ROUND_TRIP() {
+            let mut dg = DoomGram::default();
+
+            for ns in [1, 23, 456, 7_890, 12_345_678, 987_654_321_000] {
+                dg.push_event_time_ns(ns);
+            }
+
+            let json = serde_json::to_string(&dg).unwrap();
+            let dg2 : DoomGram = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(dg, dg2);
+
+            let empty : DoomGram = serde_json::from_str(&serde_json::to_string(&DoomGram::default()).unwrap()).unwrap();
+
+            assert_eq!(DoomGram::default(), empty);
+        }
+
+        #[test]
+        fn TEST_DoomGram_REJECTS_INCONSISTENT_BUCKETS() {
+            let mut dg = DoomGram::default();
+
+            dg.push_event_time_ns(7);
+            dg.push_event_time_ns(70);
+
+            let json = serde_json::to_string(&dg).unwrap();
+
+            // too many events in the buckets
+            let bad = json.replace(r#""num_events_in_1ns":1"#, r#""num_events_in_1ns":2"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+
+            // too few events in the buckets, with a non-zero minimum
+            let bad = json.replace(r#""num_events_in_1ns":1"#, r#""num_events_in_1ns":0"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+        }
+
+        #[test]
+        fn TEST_DoomGram_REJECTS_INCONSISTENT_MINMAX() {
+            let mut dg = DoomGram::default();
+
+            dg.push_event_time_ns(7);
+            dg.push_event_time_ns(70);
+
+            let json = serde_json::to_string(&dg).unwrap();
+
+            let bad = json.replace(r#""min_event_time":7"#, r#""min_event_time":700"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+
+            let bad = json.replace(r#""max_event_time":70"#, r#""max_event_time":null"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+
+            let empty = serde_json::to_string(&DoomGram::default()).unwrap();
+            let bad = empty.replace(r#""min_event_time":null"#, r#""min_event_time":0"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+        }
+
+        #[test]
+        fn TEST_DoomGram_ACCEPTS_MISSING_SUM_OF_SQUARES() {
+            let mut dg = DoomGram::default();
+
+            dg.push_event_time_ns(7);
+            dg.push_event_time_ns(70);
+
+            let json = serde_json::to_string(&dg).unwrap();
+
+            assert!(json.contains(r#","event_time_sum_sq":4949}"#));
+
+            // as serialized prior to the addition of the sum of squares
+            let old = json.replace(r#","event_time_sum_sq":4949"#, "");
+
+            let dg2 : DoomGram = serde_json::from_str(&old).unwrap();
+
+            assert_eq!(dg.to_nmmm(), dg2.to_nmmm());
+            assert_eq!(dg.to_strip(), dg2.to_strip());
+            assert!(dg2.stddev().unwrap() < 1.0);
+        }
+
+        #[test]
+        fn TEST_DoomGram_REJECTS_INCONSISTENT_SUM_OF_SQUARES() {
+            let mut dg = DoomGram::default();
+
+            dg.push_event_time_ns(7);
+            dg.push_event_time_ns(70);
+
+            let json = serde_json::to_string(&dg).unwrap();
+
+            // less than 77^2 / 2
+            let bad = json.replace(r#""event_time_sum_sq":4949"#, r#""event_time_sum_sq":2000"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+
+            // more than 77 * 70
+            let bad = json.replace(r#""event_time_sum_sq":4949"#, r#""event_time_sum_sq":6000"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+
+            let empty = serde_json::to_string(&DoomGram::default()).unwrap();
+            let bad = empty.replace(r#""event_time_sum_sq":0"#, r#""event_time_sum_sq":1"#);
+
+            assert!(serde_json::from_str::<DoomGram>(&bad).is_err());
+        }
+    }
 }
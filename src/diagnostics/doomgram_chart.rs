@@ -0,0 +1,362 @@
+// src/diagnostics/doomgram_chart.rs : bar-chart and sparkline rendering of `DoomGram`
+
+use super::{
+    doomgram::{
+        DoomGram,
+        BUCKET_LOWER_BOUNDS,
+    },
+    time_format::DurationFormat,
+};
+
+use std::fmt as std_fmt;
+
+
+/// The default width, in characters, of the bars of a
+/// [`DoomGramBarChart`].
+const DEFAULT_BAR_WIDTH : usize = 40;
+
+/// The characters of a Unicode sparkline, from lowest to highest.
+const SPARKLINE_BLOCKS : [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The characters of an ASCII sparkline, from lowest to highest.
+const SPARKLINE_ASCII : [char; 8] = ['.', ':', '-', '=', '+', '*', '#', '@'];
+
+
+/// The scaling of the bars of a [`DoomGramBarChart`], and of the levels of
+/// a sparkline, relative to the largest bucket count.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub enum ChartScale {
+    /// Proportional to the bucket count.
+    #[default]
+    Linear,
+    /// Proportional to the logarithm of (one plus) the bucket count, such
+    /// that buckets with few events remain visible alongside buckets with
+    /// many.
+    Logarithmic,
+}
+
+
+/// Multi-line horizontal bar chart of a [`DoomGram`], with one row per
+/// decade bucket, obtained from [`DoomGram::bar_chart()`] and rendered by
+/// its [`Display`](std_fmt::Display) implementation.
+///
+/// Each row comprises the lower bound of the bucket, formatted by
+/// [`nanoseconds_to_string()`](crate::nanoseconds_to_string) (or, in
+/// ASCII mode, with `us` in place of `µs`), the bar, and the bucket count.
+/// A bucket with any events has a bar of at least one character, unless
+/// the width is 0.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::DoomGram;
+///
+/// let mut dg = DoomGram::default();
+///
+/// for _ in 0..4 {
+///     dg.push_event_time_us(30);
+/// }
+/// dg.push_event_time_ms(2);
+///
+/// let chart = dg.bar_chart().width(8).to_string();
+///
+/// assert_eq!(" 10µs |########| 4", chart.lines().nth(4).unwrap());
+/// assert_eq!("  1ms |##      | 1", chart.lines().nth(6).unwrap());
+/// ```
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+pub struct DoomGramBarChart<'a> {
+    dg :    &'a DoomGram,
+    width : usize,
+    scale : ChartScale,
+    ascii : bool,
+}
+
+// API functions
+
+impl DoomGram {
+    /// Obtains a [`DoomGramBarChart`] of the instance, with linear scaling
+    /// and bars of (up to) 40 characters, not restricted to ASCII.
+    pub fn bar_chart(&self) -> DoomGramBarChart<'_> {
+        DoomGramBarChart {
+            dg :    self,
+            width : DEFAULT_BAR_WIDTH,
+            scale : ChartScale::Linear,
+            ascii : false,
+        }
+    }
+}
+
+// Mutating methods
+
+impl DoomGramBarChart<'_> {
+    /// Sets the width, in characters, of the longest bar.
+    ///
+    /// With a width of 0, no bars are drawn, even for non-empty buckets.
+    pub fn width(
+        self,
+        width : usize,
+    ) -> Self {
+        Self {
+            width,
+            ..self
+        }
+    }
+
+    /// Sets the scaling of the bars.
+    pub fn scale(
+        self,
+        scale : ChartScale,
+    ) -> Self {
+        Self {
+            scale,
+            ..self
+        }
+    }
+
+    /// Specifies whether the chart is restricted to ASCII, with the
+    /// labels writing microseconds as `us` rather than `µs`, as does
+    /// [`DoomGram::to_sparkline_ascii()`] for the sparkline.
+    pub fn ascii(
+        self,
+        ascii : bool,
+    ) -> Self {
+        Self {
+            ascii,
+            ..self
+        }
+    }
+}
+
+// Non-mutating methods
+
+impl DoomGram {
+    /// Returns a single-line sparkline of the histogram, with one Unicode
+    /// block character (`▁` to `█`) per decade bucket, or a space for an
+    /// empty bucket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::{
+    ///     ChartScale,
+    ///     DoomGram,
+    /// };
+    ///
+    /// let mut dg = DoomGram::default();
+    ///
+    /// for us in [3, 30, 30, 30, 30, 30, 30, 30, 30] {
+    ///     dg.push_event_time_us(us);
+    /// }
+    ///
+    /// assert_eq!("   ▁█       ", dg.to_sparkline(ChartScale::Linear));
+    /// assert_eq!("   ▃█       ", dg.to_sparkline(ChartScale::Logarithmic));
+    /// ```
+    pub fn to_sparkline(
+        &self,
+        scale : ChartScale,
+    ) -> String {
+        sparkline_(self, scale, &SPARKLINE_BLOCKS)
+    }
+
+    /// Like [`Self::to_sparkline()`], but using only the ASCII characters
+    /// `.:-=+*#@` (from lowest to highest), for terminals and log sinks
+    /// that cannot display the Unicode block characters.
+    pub fn to_sparkline_ascii(
+        &self,
+        scale : ChartScale,
+    ) -> String {
+        sparkline_(self, scale, &SPARKLINE_ASCII)
+    }
+}
+
+// Trait implementations
+
+impl std_fmt::Display for DoomGramBarChart<'_> {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        let buckets = self.dg.buckets_();
+        let max = buckets.iter().copied().max().unwrap_or(0);
+        let label_format = DurationFormat::new().ascii(self.ascii);
+
+        for (&count, &lower_bound) in buckets.iter().zip(BUCKET_LOWER_BOUNDS.iter()) {
+            let label = label_format.format(lower_bound as i64);
+            let len = if 0 == count || 0 == self.width {
+                0
+            } else {
+                ((scaled_fraction_(count, max, self.scale) * self.width as f64).round() as usize).max(1)
+            };
+
+            writeln!(
+                f,
+                "{:>5} |{}{}| {count}",
+                label.as_str(),
+                "#".repeat(len),
+                " ".repeat(self.width.saturating_sub(len)),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+
+// Helper functions
+
+/// Obtains the size of `count` relative to `max`, according to `scale`, in
+/// the range [0, 1].
+fn scaled_fraction_(
+    count : u64,
+    max : u64,
+    scale : ChartScale,
+) -> f64 {
+    if 0 == max {
+        return 0.0;
+    }
+
+    match scale {
+        ChartScale::Linear => count as f64 / max as f64,
+        ChartScale::Logarithmic => (count as f64).ln_1p() / (max as f64).ln_1p(),
+    }
+}
+
+fn sparkline_(
+    dg : &DoomGram,
+    scale : ChartScale,
+    levels : &[char; 8],
+) -> String {
+    let buckets = dg.buckets_();
+    let max = buckets.iter().copied().max().unwrap_or(0);
+
+    buckets
+        .iter()
+        .map(|&count| {
+            if 0 == count {
+                ' '
+            } else {
+                let level = (scaled_fraction_(count, max, scale) * levels.len() as f64).ceil() as usize;
+
+                levels[level.clamp(1, levels.len()) - 1]
+            }
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::ChartScale;
+
+    use crate::diagnostics::DoomGram;
+
+
+    fn sample_doomgram() -> DoomGram {
+        let mut dg = DoomGram::default();
+
+        for _ in 0..1_000 {
+            dg.push_event_time_us(50);
+        }
+        for _ in 0..100 {
+            dg.push_event_time_ms(5);
+        }
+        dg.push_event_time_s(20);
+
+        dg
+    }
+
+    #[test]
+    fn TEST_DoomGramBarChart_LINEAR() {
+        let dg = sample_doomgram();
+
+        let expected = concat!(
+            "  1ns |          | 0\n",
+            " 10ns |          | 0\n",
+            "100ns |          | 0\n",
+            "  1µs |          | 0\n",
+            " 10µs |##########| 1000\n",
+            "100µs |          | 0\n",
+            "  1ms |#         | 100\n",
+            " 10ms |          | 0\n",
+            "100ms |          | 0\n",
+            "   1s |          | 0\n",
+            "  10s |#         | 1\n",
+            " 100s |          | 0\n",
+        );
+
+        assert_eq!(expected, dg.bar_chart().width(10).to_string());
+    }
+
+    #[test]
+    fn TEST_DoomGramBarChart_LOGARITHMIC() {
+        let dg = sample_doomgram();
+
+        let chart = dg.bar_chart().width(30).scale(ChartScale::Logarithmic).to_string();
+        let lines : Vec<_> = chart.lines().collect();
+
+        assert_eq!(12, lines.len());
+        assert_eq!(" 10µs |##############################| 1000", lines[4]);
+        assert_eq!("  1ms |####################          | 100", lines[6]);
+        assert_eq!("  10s |###                           | 1", lines[10]);
+    }
+
+    #[test]
+    fn TEST_DoomGramBarChart_ZERO_WIDTH() {
+        let dg = sample_doomgram();
+
+        let chart = dg.bar_chart().width(0).to_string();
+        let lines : Vec<_> = chart.lines().collect();
+
+        assert_eq!(12, lines.len());
+        assert_eq!(" 10µs || 1000", lines[4]);
+        assert_eq!("  10s || 1", lines[10]);
+        assert_eq!(chart, dg.bar_chart().width(0).scale(ChartScale::Logarithmic).to_string());
+    }
+
+    #[test]
+    fn TEST_DoomGramBarChart_ascii() {
+        let dg = sample_doomgram();
+
+        let chart = dg.bar_chart().width(10).ascii(true).to_string();
+        let lines : Vec<_> = chart.lines().collect();
+
+        assert!(chart.is_ascii());
+        assert_eq!(12, lines.len());
+        assert_eq!("  1us |          | 0", lines[3]);
+        assert_eq!(" 10us |##########| 1000", lines[4]);
+        assert_eq!("100us |          | 0", lines[5]);
+        assert_eq!(chart.replace("us", "µs"), dg.bar_chart().width(10).ascii(false).to_string());
+    }
+
+    #[test]
+    fn TEST_DoomGramBarChart_EMPTY() {
+        let dg = DoomGram::default();
+
+        let chart = dg.bar_chart().to_string();
+
+        assert_eq!(12, chart.lines().count());
+        assert!(chart.lines().all(|line| line.ends_with(&format!("|{}| 0", " ".repeat(40)))));
+    }
+
+    #[test]
+    fn TEST_DoomGram_to_sparkline() {
+        let dg = sample_doomgram();
+
+        assert_eq!("    █ ▁   ▁ ", dg.to_sparkline(ChartScale::Linear));
+        assert_eq!("    █ ▆   ▁ ", dg.to_sparkline(ChartScale::Logarithmic));
+        assert_eq!("    @ .   . ", dg.to_sparkline_ascii(ChartScale::Linear));
+        assert_eq!("    @ *   . ", dg.to_sparkline_ascii(ChartScale::Logarithmic));
+
+        assert_eq!(" ".repeat(12), DoomGram::default().to_sparkline(ChartScale::Linear));
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,508 @@
+// src/diagnostics/doomgram_parse.rs : parsing of `DoomGram` strings
+
+use super::{
+    doomgram::NUM_BUCKETS,
+    gram_utils::OVERFLOW,
+    time_format::parse_nanoseconds,
+};
+
+use std::{
+    error as std_error,
+    fmt as std_fmt,
+    ops::RangeInclusive,
+};
+
+
+/// Error type returned by [`parse_doomgram_strip()`],
+/// [`parse_doomgram_mmm()`], and [`parse_doomgram_nmmm()`].
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum ParseDoomGramError {
+    /// The strip does not have exactly 12 characters.
+    InvalidStripLength {
+        length : usize,
+    },
+    /// The strip contains a character that is not `_` or `a`–`z` (or is
+    /// `*`, or a letter denoting a count too large to be represented).
+    InvalidStripCharacter {
+        position :  usize,
+        character : char,
+    },
+    /// The `<count>:` prefix of an nmmm string is missing.
+    MissingEventCount,
+    /// The `<count>:` prefix of an nmmm string is not a valid count.
+    InvalidEventCount,
+    /// The string does not have the form `<duration>` or
+    /// `<min>-<mean>-<max>`.
+    InvalidForm,
+    /// A duration is not in a form produced by
+    /// [`nanoseconds_to_string()`](crate::nanoseconds_to_string), or is
+    /// signed (see [`parse_nanoseconds()`](crate::parse_nanoseconds)).
+    InvalidDuration {
+        duration : String,
+    },
+    /// The durations are not ordered such that min <= mean <= max.
+    InconsistentDurations,
+}
+
+/// Summary values parsed from a string obtained from
+/// [`DoomGram::to_mmm()`](crate::DoomGram::to_mmm) or
+/// [`DoomGram::to_nmmm()`](crate::DoomGram::to_nmmm).
+///
+/// Since the formatting of each duration retains only around three
+/// significant digits, the parsed values are correspondingly truncated.
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct DoomGramSummary {
+    event_count :    Option<usize>,
+    has_overflowed : bool,
+    min_event_time : Option<u64>,
+    mean_event_time : Option<u64>,
+    max_event_time : Option<u64>,
+}
+
+// Non-mutating methods
+
+impl DoomGramSummary {
+    /// The event count, which is available only when parsed from an nmmm
+    /// string.
+    pub fn event_count(&self) -> Option<usize> {
+        self.event_count
+    }
+
+    /// Indicates whether the string denoted an overflowed instance.
+    pub fn has_overflowed(&self) -> bool {
+        self.has_overflowed
+    }
+
+    /// The (truncated) min event time, in nanoseconds.
+    pub fn min_event_time(&self) -> Option<u64> {
+        self.min_event_time
+    }
+
+    /// The (truncated) mean event time, in nanoseconds.
+    pub fn mean_event_time(&self) -> Option<u64> {
+        self.mean_event_time
+    }
+
+    /// The (truncated) max event time, in nanoseconds.
+    pub fn max_event_time(&self) -> Option<u64> {
+        self.max_event_time
+    }
+}
+
+// Trait implementations
+
+impl std_fmt::Display for ParseDoomGramError {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        match self {
+            Self::InvalidStripLength {
+                length,
+            } => write!(f, "strip must have {NUM_BUCKETS} characters, not {length}"),
+            Self::InvalidStripCharacter {
+                position,
+                character,
+            } => write!(f, "invalid strip character {character:?} at position {position}"),
+            Self::MissingEventCount => f.write_str("missing event count"),
+            Self::InvalidEventCount => f.write_str("invalid event count"),
+            Self::InvalidForm => f.write_str("expected <duration> or <min>-<mean>-<max>"),
+            Self::InvalidDuration {
+                duration,
+            } => write!(f, "invalid duration {duration:?}"),
+            Self::InconsistentDurations => f.write_str("durations are not ordered min <= mean <= max"),
+        }
+    }
+}
+
+
+impl std_error::Error for ParseDoomGramError {}
+
+
+// API functions
+
+/// Parses a strip obtained from
+/// [`DoomGram::to_strip()`](crate::DoomGram::to_strip) into the range of
+/// possible event counts in each of its twelve buckets.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::parse_doomgram_strip;
+///
+/// let ranges = parse_doomgram_strip("aa__a_b_____").unwrap();
+///
+/// assert_eq!(1..=9, ranges[0]);
+/// assert_eq!(0..=0, ranges[2]);
+/// assert_eq!(10..=99, ranges[6]);
+/// ```
+pub fn parse_doomgram_strip(s : &str) -> Result<[RangeInclusive<u64>; NUM_BUCKETS], ParseDoomGramError> {
+    let length = s.chars().count();
+
+    if NUM_BUCKETS != length {
+        return Err(ParseDoomGramError::InvalidStripLength {
+            length,
+        });
+    }
+
+    let mut ranges : [RangeInclusive<u64>; NUM_BUCKETS] = std::array::from_fn(|_| 0..=0);
+
+    for (position, (range, character)) in ranges.iter_mut().zip(s.chars()).enumerate() {
+        *range = match character {
+            '_' => 0..=0,
+            'a'..='t' => {
+                let doom = character as u32 - 'a' as u32;
+                let lower = 10u64.pow(doom);
+
+                lower..=lower.checked_mul(10).map_or(u64::MAX, |upper| upper - 1)
+            },
+            _ => {
+                return Err(ParseDoomGramError::InvalidStripCharacter {
+                    position,
+                    character,
+                })
+            },
+        };
+    }
+
+    Ok(ranges)
+}
+
+
+/// Parses a string obtained from
+/// [`DoomGram::to_mmm()`](crate::DoomGram::to_mmm) into its min, mean,
+/// and max event times.
+///
+/// An empty string (denoting no events) and `"OVERFLOW"` are both
+/// accepted, and produce a summary without event times.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::parse_doomgram_mmm;
+///
+/// let summary = parse_doomgram_mmm("7ns-773µs-3ms").unwrap();
+///
+/// assert_eq!(None, summary.event_count());
+/// assert_eq!(Some(7), summary.min_event_time());
+/// assert_eq!(Some(773_000), summary.mean_event_time());
+/// assert_eq!(Some(3_000_000), summary.max_event_time());
+/// ```
+pub fn parse_doomgram_mmm(s : &str) -> Result<DoomGramSummary, ParseDoomGramError> {
+    if s.is_empty() {
+        return Ok(DoomGramSummary::default());
+    }
+
+    if OVERFLOW == s {
+        return Ok(DoomGramSummary {
+            has_overflowed : true,
+            ..Default::default()
+        });
+    }
+
+    let parts : Vec<&str> = s.split('-').collect();
+
+    let (min, mean, max) = match parts.as_slice() {
+        [single] => {
+            let v = parse_duration_(single)?;
+
+            (v, v, v)
+        },
+        [min, mean, max] => (parse_duration_(min)?, parse_duration_(mean)?, parse_duration_(max)?),
+        _ => return Err(ParseDoomGramError::InvalidForm),
+    };
+
+    if min > mean || mean > max {
+        return Err(ParseDoomGramError::InconsistentDurations);
+    }
+
+    Ok(DoomGramSummary {
+        event_count : None,
+        has_overflowed : false,
+        min_event_time : Some(min),
+        mean_event_time : Some(mean),
+        max_event_time : Some(max),
+    })
+}
+
+
+/// Parses a string obtained from
+/// [`DoomGram::to_nmmm()`](crate::DoomGram::to_nmmm) into its event count
+/// and min, mean, and max event times.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::parse_doomgram_nmmm;
+///
+/// let summary = parse_doomgram_nmmm("4:7ns-773µs-3ms").unwrap();
+///
+/// assert_eq!(Some(4), summary.event_count());
+/// assert_eq!(Some(7), summary.min_event_time());
+/// assert_eq!(Some(773_000), summary.mean_event_time());
+/// assert_eq!(Some(3_000_000), summary.max_event_time());
+/// ```
+pub fn parse_doomgram_nmmm(s : &str) -> Result<DoomGramSummary, ParseDoomGramError> {
+    let (count, mmm) = s.split_once(':').ok_or(ParseDoomGramError::MissingEventCount)?;
+
+    if count.is_empty() || !count.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseDoomGramError::InvalidEventCount);
+    }
+
+    let event_count = count.parse::<usize>().map_err(|_| ParseDoomGramError::InvalidEventCount)?;
+
+    let summary = parse_doomgram_mmm(mmm)?;
+
+    // the event times are present if, and only if, there are events and
+    // no overflow
+    if (0 == event_count) != (summary.min_event_time.is_none() && !summary.has_overflowed) {
+        return Err(ParseDoomGramError::InvalidForm);
+    }
+
+    Ok(DoomGramSummary {
+        event_count : Some(event_count),
+        ..summary
+    })
+}
+
+
+// Helper functions
+
+fn parse_duration_(s : &str) -> Result<u64, ParseDoomGramError> {
+    match parse_nanoseconds(s) {
+        Ok(v) if !s.starts_with(['-', '+']) => Ok(v as u64),
+        _ => Err(ParseDoomGramError::InvalidDuration {
+            duration : s.into(),
+        }),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        parse_doomgram_mmm,
+        parse_doomgram_nmmm,
+        parse_doomgram_strip,
+        ParseDoomGramError,
+    };
+
+    use crate::diagnostics::{
+        nanoseconds_to_string,
+        DoomGram,
+    };
+
+
+    fn sample_doomgram() -> DoomGram {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(11);
+        dg.push_event_time_ns(19);
+        dg.push_event_time_ns(19);
+        dg.push_event_time_us(7);
+        dg.push_event_time_us(7);
+        dg.push_event_time_us(89);
+        dg.push_event_time_ms(248);
+        dg.push_event_time_ms(4_321);
+        dg.push_event_time_s(5);
+        dg.push_event_time_s(309);
+
+        dg
+    }
+
+
+    #[test]
+    fn TEST_parse_doomgram_strip_ROUND_TRIP() {
+        let dg = sample_doomgram();
+
+        let ranges = parse_doomgram_strip(&dg.to_strip()).unwrap();
+
+        for (range, count) in ranges.iter().zip(dg.buckets_()) {
+            assert!(range.contains(&count), "{count} not in {range:?}");
+        }
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_strip_ALL_CHARACTERS() {
+        let ranges = parse_doomgram_strip("_abcdefghijt").unwrap();
+
+        assert_eq!(0..=0, ranges[0]);
+        assert_eq!(1..=9, ranges[1]);
+        assert_eq!(10..=99, ranges[2]);
+        assert_eq!(100..=999, ranges[3]);
+        assert_eq!(1_000_000_000..=9_999_999_999, ranges[10]);
+        assert_eq!(10_000_000_000_000_000_000..=u64::MAX, ranges[11]);
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_strip_INVALID() {
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidStripLength {
+                length : 11,
+            }),
+            parse_doomgram_strip("aa__a_a____"),
+        );
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidStripLength {
+                length : 13,
+            }),
+            parse_doomgram_strip("aa__a_a______"),
+        );
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidStripCharacter {
+                position :  3,
+                character : 'A',
+            }),
+            parse_doomgram_strip("aa_Aa_a_____"),
+        );
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidStripCharacter {
+                position :  0,
+                character : 'u',
+            }),
+            parse_doomgram_strip("ua__a_a_____"),
+        );
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidStripCharacter {
+                position :  11,
+                character : '*',
+            }),
+            parse_doomgram_strip("aa__a_a____*"),
+        );
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_mmm_FORMS() {
+        {
+            let summary = parse_doomgram_mmm("").unwrap();
+
+            assert_eq!(None, summary.event_count());
+            assert!(!summary.has_overflowed());
+            assert_eq!(None, summary.min_event_time());
+        }
+
+        {
+            let summary = parse_doomgram_mmm("OVERFLOW").unwrap();
+
+            assert!(summary.has_overflowed());
+            assert_eq!(None, summary.min_event_time());
+        }
+
+        {
+            let summary = parse_doomgram_mmm("13ms").unwrap();
+
+            assert_eq!(Some(13_000_000), summary.min_event_time());
+            assert_eq!(Some(13_000_000), summary.mean_event_time());
+            assert_eq!(Some(13_000_000), summary.max_event_time());
+        }
+
+        {
+            let summary = parse_doomgram_mmm("10ns-31.85s-309s").unwrap();
+
+            assert_eq!(Some(10), summary.min_event_time());
+            assert_eq!(Some(31_850_000_000), summary.mean_event_time());
+            assert_eq!(Some(309_000_000_000), summary.max_event_time());
+        }
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_mmm_INVALID() {
+        assert_eq!(Err(ParseDoomGramError::InvalidForm), parse_doomgram_mmm("1s-2s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidForm), parse_doomgram_mmm("1s-2s-3s-4s"));
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidDuration {
+                duration : "2x".into(),
+            }),
+            parse_doomgram_mmm("1s-2x-3s"),
+        );
+        assert_eq!(Err(ParseDoomGramError::InvalidForm), parse_doomgram_mmm("-1s"));
+        assert_eq!(
+            Err(ParseDoomGramError::InvalidDuration {
+                duration : "+1s".into(),
+            }),
+            parse_doomgram_mmm("+1s"),
+        );
+        assert_eq!(Err(ParseDoomGramError::InconsistentDurations), parse_doomgram_mmm("3s-2s-1s"));
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_nmmm_FORMS() {
+        {
+            let summary = parse_doomgram_nmmm("0:").unwrap();
+
+            assert_eq!(Some(0), summary.event_count());
+            assert_eq!(None, summary.min_event_time());
+        }
+
+        {
+            let summary = parse_doomgram_nmmm("3:OVERFLOW").unwrap();
+
+            assert_eq!(Some(3), summary.event_count());
+            assert!(summary.has_overflowed());
+        }
+
+        {
+            let summary = parse_doomgram_nmmm("2:1s-1.500s-2s").unwrap();
+
+            assert_eq!(Some(2), summary.event_count());
+            assert_eq!(Some(1_000_000_000), summary.min_event_time());
+            assert_eq!(Some(1_500_000_000), summary.mean_event_time());
+            assert_eq!(Some(2_000_000_000), summary.max_event_time());
+        }
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_nmmm_INVALID() {
+        assert_eq!(Err(ParseDoomGramError::MissingEventCount), parse_doomgram_nmmm("1s-2s-3s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidEventCount), parse_doomgram_nmmm(":1s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidEventCount), parse_doomgram_nmmm("-1:1s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidEventCount), parse_doomgram_nmmm("x:1s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidForm), parse_doomgram_nmmm("0:1s"));
+        assert_eq!(Err(ParseDoomGramError::InvalidForm), parse_doomgram_nmmm("2:"));
+    }
+
+    #[test]
+    fn TEST_parse_doomgram_nmmm_ROUND_TRIP() {
+        let dg = sample_doomgram();
+
+        let summary = parse_doomgram_nmmm(&dg.to_nmmm()).unwrap();
+
+        assert_eq!(Some(dg.event_count()), summary.event_count());
+        assert_eq!(dg.min_event_time(), summary.min_event_time());
+        assert_eq!(dg.max_event_time(), summary.max_event_time());
+        assert_eq!(Some(31_850_000_000), summary.mean_event_time());
+
+        // formatting the parsed values reproduces the original string
+        let reformatted = format!(
+            "{}-{}-{}",
+            nanoseconds_to_string(summary.min_event_time().unwrap() as i64, ""),
+            nanoseconds_to_string(summary.mean_event_time().unwrap() as i64, ""),
+            nanoseconds_to_string(summary.max_event_time().unwrap() as i64, ""),
+        );
+
+        assert_eq!(dg.to_mmm(), reformatted);
+    }
+
+    #[test]
+    fn TEST_ParseDoomGramError_Display() {
+        assert_eq!(
+            "invalid strip character 'A' at position 3",
+            ParseDoomGramError::InvalidStripCharacter {
+                position :  3,
+                character : 'A',
+            }
+            .to_string(),
+        );
+        assert_eq!("missing event count", ParseDoomGramError::MissingEventCount.to_string());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,580 @@
+// src/diagnostics/doomgram_prometheus.rs : Prometheus text exposition of `DoomGram`
+
+use super::doomgram::{
+    DoomGram,
+    NUM_BUCKETS,
+};
+
+use std::{
+    error as std_error,
+    fmt as std_fmt,
+};
+
+
+/// The `le` (inclusive upper bound) value, in seconds, of each bucket of
+/// the exposed histogram, being the greatest whole number of nanoseconds
+/// in each decade, from 9ns to 99,999,999,999ns, and then `+Inf`.
+const LE_VALUES : [&str; NUM_BUCKETS] = [
+    "9e-09",
+    "9.9e-08",
+    "9.99e-07",
+    "9.999e-06",
+    "9.9999e-05",
+    "0.000999999",
+    "0.009999999",
+    "0.099999999",
+    "0.999999999",
+    "9.999999999",
+    "99.999999999",
+    "+Inf",
+];
+
+
+/// Error type returned by [`write_doomgram_prometheus()`] and
+/// [`doomgram_to_prometheus()`].
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum WritePrometheusError {
+    /// The metric name does not match `[a-zA-Z_:][a-zA-Z0-9_:]*`.
+    InvalidMetricName {
+        name : String,
+    },
+    /// A label name does not match `[a-zA-Z_][a-zA-Z0-9_]*`, or begins
+    /// with the reserved prefix `__`.
+    InvalidLabelName {
+        name : String,
+    },
+    /// A label name is `le`, which is reserved for histogram buckets.
+    ReservedLabelName {
+        name : String,
+    },
+    /// A label name is specified more than once.
+    DuplicateLabelName {
+        name : String,
+    },
+    /// The underlying writer failed.
+    Fmt(std_fmt::Error),
+}
+
+// Trait implementations
+
+impl std_fmt::Display for WritePrometheusError {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        match self {
+            Self::InvalidMetricName {
+                name,
+            } => write!(f, "invalid metric name {name:?}"),
+            Self::InvalidLabelName {
+                name,
+            } => write!(f, "invalid label name {name:?}"),
+            Self::ReservedLabelName {
+                name,
+            } => write!(f, "reserved label name {name:?}"),
+            Self::DuplicateLabelName {
+                name,
+            } => write!(f, "duplicate label name {name:?}"),
+            Self::Fmt(e) => write!(f, "write failed: {e}"),
+        }
+    }
+}
+
+
+impl std_error::Error for WritePrometheusError {
+    fn source(&self) -> Option<&(dyn std_error::Error + 'static)> {
+        match self {
+            Self::Fmt(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+
+impl From<std_fmt::Error> for WritePrometheusError {
+    fn from(e : std_fmt::Error) -> Self {
+        Self::Fmt(e)
+    }
+}
+
+
+// API functions
+
+/// Writes a [`DoomGram`] as a cumulative histogram in the Prometheus text
+/// exposition format.
+///
+/// Following Prometheus convention, times are expressed in seconds, so
+/// `metric_name` should ordinarily end with `_seconds`. Since a
+/// [`DoomGram`] decade includes its lower bound but excludes its upper,
+/// the `le` of each bucket is the greatest whole number of nanoseconds in
+/// the decade, from 9ns (`"9e-09"`) to 99,999,999,999ns
+/// (`"99.999999999"`), plus `"+Inf"`, so that each bucket counts the
+/// events no greater than its `le`; zero-time events are counted in the
+/// first bucket.
+///
+/// # Parameters
+///
+/// * `w` — the writer to which the exposition is written;
+/// * `dg` — the gram to be written;
+/// * `metric_name` — the metric name, to which the `_bucket`, `_sum`, and
+///   `_count` suffixes are appended;
+/// * `labels` — name/value pairs of labels to be applied to every sample,
+///   in the order given; values are escaped as required;
+///
+/// # Note
+/// If the gram has overflowed, `_sum` is written as `+Inf`.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     write_doomgram_prometheus,
+///     DoomGram,
+/// };
+///
+/// let mut dg = DoomGram::default();
+///
+/// dg.push_event_time_ms(3);
+///
+/// let mut s = String::new();
+///
+/// write_doomgram_prometheus(&mut s, &dg, "request_seconds", &[("path", "/")]).unwrap();
+///
+/// assert!(s.starts_with("# TYPE request_seconds histogram\n"));
+/// assert!(s.contains("request_seconds_bucket{path=\"/\",le=\"0.000999999\"} 0\n"));
+/// assert!(s.contains("request_seconds_bucket{path=\"/\",le=\"0.009999999\"} 1\n"));
+/// assert!(s.ends_with("request_seconds_sum{path=\"/\"} 0.003\nrequest_seconds_count{path=\"/\"} 1\n"));
+/// ```
+pub fn write_doomgram_prometheus<W : std_fmt::Write + ?Sized>(
+    w : &mut W,
+    dg : &DoomGram,
+    metric_name : &str,
+    labels : &[(&str, &str)],
+) -> Result<(), WritePrometheusError> {
+    validate_metric_name_(metric_name)?;
+
+    for (i, &(name, _)) in labels.iter().enumerate() {
+        validate_label_name_(name)?;
+
+        if labels[..i].iter().any(|&(prior, _)| prior == name) {
+            return Err(WritePrometheusError::DuplicateLabelName {
+                name : name.into(),
+            });
+        }
+    }
+
+    let mut label_str = String::new();
+
+    for (i, &(name, value)) in labels.iter().enumerate() {
+        if 0 != i {
+            label_str.push(',');
+        }
+
+        label_str.push_str(name);
+        label_str.push_str("=\"");
+        push_escaped_label_value_(&mut label_str, value);
+        label_str.push('"');
+    }
+
+    let separator = if label_str.is_empty() { "" } else { "," };
+
+    writeln!(w, "# TYPE {metric_name} histogram")?;
+
+    // zero-time events are not bucketed, so are included up-front
+    let buckets = dg.buckets_();
+    let bucket_total = buckets.iter().fold(0u64, |sum, &n| sum.saturating_add(n));
+    let mut cumulative = (dg.event_count() as u64).saturating_sub(bucket_total);
+
+    for (n, le) in buckets.iter().zip(LE_VALUES) {
+        cumulative = cumulative.saturating_add(*n);
+
+        writeln!(w, "{metric_name}_bucket{{{label_str}{separator}le=\"{le}\"}} {cumulative}")?;
+    }
+
+    let braced_labels = if label_str.is_empty() {
+        String::new()
+    } else {
+        format!("{{{label_str}}}")
+    };
+
+    if dg.has_overflowed() {
+        writeln!(w, "{metric_name}_sum{braced_labels} +Inf")?;
+    } else {
+        let total_ns = dg.event_time_total_raw();
+        let whole = total_ns / 1_000_000_000;
+        let frac = total_ns % 1_000_000_000;
+
+        if 0 == frac {
+            writeln!(w, "{metric_name}_sum{braced_labels} {whole}")?;
+        } else {
+            let frac = format!("{frac:09}");
+
+            writeln!(w, "{metric_name}_sum{braced_labels} {whole}.{}", frac.trim_end_matches('0'))?;
+        }
+    }
+
+    writeln!(w, "{metric_name}_count{braced_labels} {}", dg.event_count())?;
+
+    Ok(())
+}
+
+/// Obtains a [`DoomGram`] as a cumulative histogram in the Prometheus text
+/// exposition format.
+///
+/// See [`write_doomgram_prometheus()`] for details.
+pub fn doomgram_to_prometheus(
+    dg : &DoomGram,
+    metric_name : &str,
+    labels : &[(&str, &str)],
+) -> Result<String, WritePrometheusError> {
+    let mut s = String::new();
+
+    write_doomgram_prometheus(&mut s, dg, metric_name, labels)?;
+
+    Ok(s)
+}
+
+
+// Helper functions
+
+fn validate_metric_name_(name : &str) -> Result<(), WritePrometheusError> {
+    let mut bytes = name.bytes();
+
+    let is_valid = match bytes.next() {
+        Some(b) if b.is_ascii_alphabetic() || b'_' == b || b':' == b => {
+            bytes.all(|b| b.is_ascii_alphanumeric() || b'_' == b || b':' == b)
+        },
+        _ => false,
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(WritePrometheusError::InvalidMetricName {
+            name : name.into(),
+        })
+    }
+}
+
+fn validate_label_name_(name : &str) -> Result<(), WritePrometheusError> {
+    let mut bytes = name.bytes();
+
+    let is_valid = match bytes.next() {
+        Some(b) if b.is_ascii_alphabetic() || b'_' == b => bytes.all(|b| b.is_ascii_alphanumeric() || b'_' == b),
+        _ => false,
+    };
+
+    if !is_valid || name.starts_with("__") {
+        return Err(WritePrometheusError::InvalidLabelName {
+            name : name.into(),
+        });
+    }
+
+    if "le" == name {
+        return Err(WritePrometheusError::ReservedLabelName {
+            name : name.into(),
+        });
+    }
+
+    Ok(())
+}
+
+fn push_escaped_label_value_(
+    s : &mut String,
+    value : &str,
+) {
+    for c in value.chars() {
+        match c {
+            '\\' => s.push_str("\\\\"),
+            '"' => s.push_str("\\\""),
+            '\n' => s.push_str("\\n"),
+            c => s.push(c),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        doomgram_to_prometheus,
+        write_doomgram_prometheus,
+        WritePrometheusError,
+    };
+
+    use crate::diagnostics::DoomGram;
+
+    use std::collections::BTreeMap;
+
+
+    /// Checks that `text` is a well-formed Prometheus text exposition of a
+    /// single histogram, after the manner of `promtool check metrics`,
+    /// returning the sample count.
+    fn check_histogram_exposition(text : &str) -> u64 {
+        assert!(text.ends_with('\n'), "exposition must end with a newline");
+
+        let mut lines = text.lines();
+
+        let type_line = lines.next().expect("missing # TYPE line");
+        let mut type_fields = type_line.split(' ');
+
+        assert_eq!(Some("#"), type_fields.next());
+        assert_eq!(Some("TYPE"), type_fields.next());
+
+        let name = type_fields.next().expect("missing metric name");
+
+        assert_eq!(Some("histogram"), type_fields.next());
+        assert_eq!(None, type_fields.next());
+        assert!(is_valid_metric_name(name), "invalid metric name {name:?}");
+
+        let mut bucket_label_sets = Vec::new();
+        let mut prev_le = f64::NEG_INFINITY;
+        let mut prev_bucket = 0u64;
+        let mut inf_bucket = None;
+        let mut sum_labels = None;
+        let mut count = None;
+
+        for line in lines {
+            assert!(!line.starts_with('#'), "unexpected comment line {line:?}");
+
+            let (series, value) = line.rsplit_once(' ').expect("missing sample value");
+            let (sample_name, labels) = parse_series(series);
+
+            if sample_name == format!("{name}_bucket") {
+                assert!(sum_labels.is_none() && count.is_none(), "bucket after _sum/_count");
+
+                let mut labels = labels;
+                let le = labels.remove("le").expect("bucket missing le label");
+                let le = match le.as_str() {
+                    "+Inf" => f64::INFINITY,
+                    le => le.parse::<f64>().expect("invalid le value"),
+                };
+                let v = value.parse::<u64>().expect("invalid bucket value");
+
+                assert!(le > prev_le, "le values not strictly increasing");
+                assert!(v >= prev_bucket, "bucket values not cumulative");
+
+                prev_le = le;
+                prev_bucket = v;
+
+                if f64::INFINITY == le {
+                    inf_bucket = Some(v);
+                }
+
+                bucket_label_sets.push(labels);
+            } else if sample_name == format!("{name}_sum") {
+                assert!(value.parse::<f64>().is_ok() || "+Inf" == value, "invalid _sum value");
+
+                sum_labels = Some(labels);
+            } else if sample_name == format!("{name}_count") {
+                assert!(sum_labels.is_some(), "_count before _sum");
+                assert!(bucket_label_sets.iter().all(|l| *l == labels), "inconsistent labels");
+                assert_eq!(sum_labels.as_ref(), Some(&labels), "inconsistent labels");
+
+                count = Some(value.parse::<u64>().expect("invalid _count value"));
+            } else {
+                panic!("unexpected sample {sample_name:?}");
+            }
+        }
+
+        let count = count.expect("missing _count");
+
+        assert_eq!(Some(count), inf_bucket, "+Inf bucket must equal _count");
+
+        count
+    }
+
+    fn is_valid_metric_name(name : &str) -> bool {
+        !name.is_empty()
+            && !name.starts_with(|c : char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || '_' == c || ':' == c)
+    }
+
+    fn parse_series(series : &str) -> (&str, BTreeMap<String, String>) {
+        let mut labels = BTreeMap::new();
+
+        let Some((name, rest)) = series.split_once('{') else {
+            return (series, labels);
+        };
+
+        let mut chars = rest.strip_suffix('}').expect("unterminated label set").chars().peekable();
+
+        while chars.peek().is_some() {
+            let label_name : String = chars.by_ref().take_while(|&c| '=' != c).collect();
+
+            assert!(
+                label_name.chars().all(|c| c.is_ascii_alphanumeric() || '_' == c),
+                "invalid label name {label_name:?}"
+            );
+            assert_eq!(Some('"'), chars.next(), "label value must be quoted");
+
+            let mut value = String::new();
+
+            loop {
+                match chars.next().expect("unterminated label value") {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('\\') => value.push('\\'),
+                        Some('"') => value.push('"'),
+                        Some('n') => value.push('\n'),
+                        c => panic!("invalid escape {c:?}"),
+                    },
+                    '\n' => panic!("raw newline in label value"),
+                    c => value.push(c),
+                }
+            }
+
+            assert!(labels.insert(label_name, value).is_none(), "duplicate label");
+
+            match chars.next() {
+                Some(',') | None => (),
+                c => panic!("unexpected {c:?} after label value"),
+            }
+        }
+
+        (name, labels)
+    }
+
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_EMPTY() {
+        let dg = DoomGram::default();
+
+        let text = doomgram_to_prometheus(&dg, "op_seconds", &[]).unwrap();
+
+        assert_eq!(0, check_histogram_exposition(&text));
+        assert!(text.contains("op_seconds_bucket{le=\"9e-09\"} 0\n"));
+        assert!(text.contains("op_seconds_bucket{le=\"+Inf\"} 0\n"));
+        assert!(text.ends_with("op_seconds_sum 0\nop_seconds_count 0\n"));
+    }
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_EXACT_OUTPUT() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(0);
+        dg.push_event_time_ns(7);
+        dg.push_event_time_us(5);
+        dg.push_event_time_ms(3);
+        dg.push_event_time_ms(4);
+        dg.push_event_time_s(200);
+
+        let text = doomgram_to_prometheus(&dg, "op_seconds", &[("job", "x")]).unwrap();
+
+        let expected = "\
+# TYPE op_seconds histogram
+op_seconds_bucket{job=\"x\",le=\"9e-09\"} 2
+op_seconds_bucket{job=\"x\",le=\"9.9e-08\"} 2
+op_seconds_bucket{job=\"x\",le=\"9.99e-07\"} 2
+op_seconds_bucket{job=\"x\",le=\"9.999e-06\"} 3
+op_seconds_bucket{job=\"x\",le=\"9.9999e-05\"} 3
+op_seconds_bucket{job=\"x\",le=\"0.000999999\"} 3
+op_seconds_bucket{job=\"x\",le=\"0.009999999\"} 5
+op_seconds_bucket{job=\"x\",le=\"0.099999999\"} 5
+op_seconds_bucket{job=\"x\",le=\"0.999999999\"} 5
+op_seconds_bucket{job=\"x\",le=\"9.999999999\"} 5
+op_seconds_bucket{job=\"x\",le=\"99.999999999\"} 5
+op_seconds_bucket{job=\"x\",le=\"+Inf\"} 6
+op_seconds_sum{job=\"x\"} 200.007005007
+op_seconds_count{job=\"x\"} 6
+";
+
+        assert_eq!(expected, text);
+        assert_eq!(6, check_histogram_exposition(&text));
+    }
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_DECADE_BOUNDARIES() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(999_999);
+        dg.push_event_time_ms(1);
+
+        let text = doomgram_to_prometheus(&dg, "op_seconds", &[]).unwrap();
+
+        // each event is counted in the first bucket whose `le` it does not
+        // exceed
+        assert_eq!(2, check_histogram_exposition(&text));
+        assert!(text.contains("op_seconds_bucket{le=\"0.000999999\"} 1\n"));
+        assert!(text.contains("op_seconds_bucket{le=\"0.009999999\"} 2\n"));
+    }
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_ESCAPES_LABEL_VALUES() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ms(12);
+
+        let text = doomgram_to_prometheus(&dg, "ns:op_seconds", &[("path", "a\\b\"c\nd"), ("method", "GET")]).unwrap();
+
+        assert_eq!(1, check_histogram_exposition(&text));
+        assert!(text.contains("ns:op_seconds_sum{path=\"a\\\\b\\\"c\\nd\",method=\"GET\"} 0.012\n"));
+    }
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_OVERFLOW() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(u64::MAX - 1);
+        dg.push_event_time_ns(2);
+
+        assert!(dg.has_overflowed());
+
+        let text = doomgram_to_prometheus(&dg, "op_seconds", &[]).unwrap();
+
+        assert_eq!(1, check_histogram_exposition(&text));
+        assert!(text.contains("op_seconds_sum +Inf\n"));
+    }
+
+    #[test]
+    fn TEST_doomgram_to_prometheus_INVALID_NAMES() {
+        type Labels<'a> = &'a [(&'a str, &'a str)];
+
+        let dg = DoomGram::default();
+
+        #[rustfmt::skip]
+        let cases : [(&str, Labels, WritePrometheusError); 7] = [
+            ("",       &[],                       WritePrometheusError::InvalidMetricName { name : "".into() }),
+            ("1op",    &[],                       WritePrometheusError::InvalidMetricName { name : "1op".into() }),
+            ("op-s",   &[],                       WritePrometheusError::InvalidMetricName { name : "op-s".into() }),
+            ("op",     &[("a:b", "")],            WritePrometheusError::InvalidLabelName { name : "a:b".into() }),
+            ("op",     &[("__x", "")],            WritePrometheusError::InvalidLabelName { name : "__x".into() }),
+            ("op",     &[("le", "")],             WritePrometheusError::ReservedLabelName { name : "le".into() }),
+            ("op",     &[("a", ""), ("a", "")],   WritePrometheusError::DuplicateLabelName { name : "a".into() }),
+        ];
+
+        for (name, labels, expected) in cases {
+            assert_eq!(Err(expected), doomgram_to_prometheus(&dg, name, labels));
+        }
+    }
+
+    #[test]
+    fn TEST_write_doomgram_prometheus_APPENDS() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_us(20);
+
+        let mut s = String::from("# preceding\n");
+
+        write_doomgram_prometheus(&mut s, &dg, "a_seconds", &[]).unwrap();
+
+        assert!(s.starts_with("# preceding\n# TYPE a_seconds histogram\n"));
+        assert!(s.contains("a_seconds_bucket{le=\"9.9999e-05\"} 1\n"));
+    }
+
+    #[test]
+    fn TEST_WritePrometheusError_Display() {
+        let e = WritePrometheusError::ReservedLabelName {
+            name : "le".into(),
+        };
+
+        assert_eq!("reserved label name \"le\"", e.to_string());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,519 @@
+// src/diagnostics/doomgram_registry.rs : `DoomGramRegistry`
+
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doomgram::DoomGram,
+};
+
+use std::{
+    collections::BTreeMap,
+    io as std_io,
+    mem as std_mem,
+    sync::{
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+    time::Duration,
+};
+
+
+/// A thread-safe collection of [`DoomGram`]s keyed by name, from which a
+/// sorted report of all grams may be written.
+///
+/// A process-wide instance is available from [`Self::global()`]; scoped
+/// instances (e.g. one per service) may be created with [`Self::new()`].
+/// Grams are created on first use of their name.
+///
+/// See also [`doom_registry_scope!`](crate::doom_registry_scope), which
+/// uses the name of the enclosing function when no name is given.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::DoomGramRegistry;
+///
+/// let registry = DoomGramRegistry::new();
+///
+/// registry.push_event_time_ns("db::query", 3_000_000);
+/// registry.push_event_time_ns("db::query", 5_000_000);
+/// registry.push_event_time_ns("cache::get", 700);
+///
+/// let mut report = Vec::new();
+///
+/// registry.report(&mut report).unwrap();
+///
+/// assert_eq!(
+///     "\
+/// name        nmmm           strip
+/// cache::get  1:700ns        __a_________
+/// db::query   2:3ms-4ms-5ms  ______a_____
+/// ",
+///     String::from_utf8(report).unwrap(),
+/// );
+/// ```
+#[derive(Debug)]
+#[derive(Default)]
+pub struct DoomGramRegistry {
+    grams : Mutex<BTreeMap<String, DoomGram>>,
+}
+
+// API functions
+
+impl DoomGramRegistry {
+    /// Creates an empty instance.
+    pub const fn new() -> Self {
+        Self {
+            grams : Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// The process-wide instance.
+    pub fn global() -> &'static Self {
+        static GLOBAL : DoomGramRegistry = DoomGramRegistry::new();
+
+        &GLOBAL
+    }
+}
+
+// Mutating methods
+
+impl DoomGramRegistry {
+    /// Removes all grams.
+    pub fn clear(&self) {
+        self.lock_().clear();
+    }
+
+    /// Removes the named gram, returning it if it existed.
+    pub fn remove(
+        &self,
+        name : &str,
+    ) -> Option<DoomGram> {
+        self.lock_().remove(name)
+    }
+
+    /// Clears every gram, retaining their names.
+    pub fn reset(&self) {
+        self.lock_().values_mut().for_each(DoomGram::clear);
+    }
+
+    /// Pushes an event with the given number of nanoseconds into the named
+    /// gram, creating it if necessary.
+    pub fn push_event_time_ns(
+        &self,
+        name : &str,
+        time_in_ns : u64,
+    ) -> bool {
+        self.with_gram(name, |dg| dg.push_event_time_ns(time_in_ns))
+    }
+
+    /// Pushes an event with the given [`Duration`] into the named gram,
+    /// creating it if necessary.
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &self,
+        name : &str,
+        duration : Duration,
+    ) -> bool {
+        self.push_event_time_ns(name, duration.as_nanos() as u64)
+    }
+
+    /// Invokes `f` with the named gram, creating it if necessary.
+    ///
+    /// # Note:
+    /// The registry is locked for the duration of `f`.
+    pub fn with_gram<F, R>(
+        &self,
+        name : &str,
+        f : F,
+    ) -> R
+    where
+        F : FnOnce(&mut DoomGram) -> R,
+    {
+        let mut grams = self.lock_();
+
+        match grams.get_mut(name) {
+            Some(dg) => f(dg),
+            None => f(grams.entry(name.into()).or_default()),
+        }
+    }
+
+    /// Executes a closure, records its elapsed time in the named gram
+    /// (creating it if necessary), and returns the closure's result
+    /// together with the measured elapsed time (in nanoseconds).
+    ///
+    /// The registry is not locked while the closure executes.
+    pub fn doom_scope<F, R>(
+        &self,
+        name : &str,
+        work : F,
+    ) -> (
+        R,   // work_result
+        u64, // measured_elapsed_time_in_ns
+    )
+    where
+        F : FnOnce() -> R,
+    {
+        self.doom_scope_with_clock(name, InstantClock, work)
+    }
+
+    /// Executes a closure, records its elapsed time, as measured by the
+    /// given [`Clock`], in the named gram (creating it if necessary), and
+    /// returns the closure's result together with the measured elapsed
+    /// time (in nanoseconds).
+    ///
+    /// The registry is not locked while the closure executes.
+    pub fn doom_scope_with_clock<C, F, R>(
+        &self,
+        name : &str,
+        clock : C,
+        work : F,
+    ) -> (
+        R,   // work_result
+        u64, // measured_elapsed_time_in_ns
+    )
+    where
+        C : Clock,
+        F : FnOnce() -> R,
+    {
+        let before = clock.now_ns();
+
+        let work_result = work();
+
+        let after = clock.now_ns();
+
+        let measured_elapsed_time_in_ns = after.saturating_sub(before);
+
+        self.push_event_time_ns(name, measured_elapsed_time_in_ns);
+
+        (
+            work_result,
+            measured_elapsed_time_in_ns,
+        )
+    }
+
+    /// Writes a report of all grams, as [`Self::report()`], and then clears
+    /// every gram (retaining their names), such that each report covers
+    /// only the events since the previous one.
+    ///
+    /// The capture and the reset are performed atomically with respect to
+    /// the recording of events.
+    pub fn report_and_reset<W : std_io::Write>(
+        &self,
+        w : &mut W,
+    ) -> std_io::Result<()> {
+        let grams = {
+            let mut grams = self.lock_();
+
+            grams.iter_mut().map(|(name, dg)| (name.clone(), std_mem::take(dg))).collect()
+        };
+
+        write_report_(w, &grams)
+    }
+}
+
+// Non-mutating methods
+
+impl DoomGramRegistry {
+    /// Obtains a copy of the named gram, if it exists.
+    pub fn get(
+        &self,
+        name : &str,
+    ) -> Option<DoomGram> {
+        self.lock_().get(name).cloned()
+    }
+
+    /// Indicates whether the registry contains no grams.
+    pub fn is_empty(&self) -> bool {
+        self.lock_().is_empty()
+    }
+
+    /// The number of grams.
+    pub fn len(&self) -> usize {
+        self.lock_().len()
+    }
+
+    /// The names of all grams, in sorted order.
+    pub fn names(&self) -> Vec<String> {
+        self.lock_().keys().cloned().collect()
+    }
+
+    /// Writes a report of all grams to `w`, as a table, sorted by name, of
+    /// the name, [`DoomGram::to_nmmm()`], and [`DoomGram::to_strip()`] of
+    /// each gram, preceded by a header line.
+    pub fn report<W : std_io::Write>(
+        &self,
+        w : &mut W,
+    ) -> std_io::Result<()> {
+        let grams = self.lock_().clone();
+
+        write_report_(w, &grams)
+    }
+}
+
+// Implementation
+
+impl DoomGramRegistry {
+    fn lock_(&self) -> MutexGuard<'_, BTreeMap<String, DoomGram>> {
+        // the grams remain consistent even if a holder panicked
+        self.grams.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+
+// Helper functions
+
+fn write_report_<W : std_io::Write>(
+    w : &mut W,
+    grams : &BTreeMap<String, DoomGram>,
+) -> std_io::Result<()> {
+    const NAME : &str = "name";
+    const NMMM : &str = "nmmm";
+    const STRIP : &str = "strip";
+
+    let rows : Vec<_> = grams
+        .iter()
+        .map(|(name, dg)| (name.as_str(), dg.to_nmmm(), dg.to_strip()))
+        .collect();
+
+    let name_width = rows.iter().map(|(name, ..)| name.chars().count()).fold(NAME.len(), usize::max);
+    let nmmm_width = rows.iter().map(|(_, nmmm, _)| nmmm.chars().count()).fold(NMMM.len(), usize::max);
+
+    writeln!(w, "{NAME:name_width$}  {NMMM:nmmm_width$}  {STRIP}")?;
+
+    for (name, nmmm, strip) in rows {
+        writeln!(w, "{name:name_width$}  {nmmm:nmmm_width$}  {strip}")?;
+    }
+
+    Ok(())
+}
+
+
+/// Executes a closure, records its elapsed time in the named gram of a
+/// [`DoomGramRegistry`], and returns the closure's result together with the
+/// measured elapsed time (in nanoseconds).
+///
+/// If no name is given, the fully-qualified name of the enclosing function,
+/// as obtained from [`function_fully_qualified_name!`][ffqn], is used.
+///
+/// [ffqn]: crate::function_fully_qualified_name
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     doom_registry_scope,
+///     DoomGramRegistry,
+/// };
+///
+/// fn load_config(registry : &DoomGramRegistry) -> usize {
+///     let (len, _elapsed_ns) = doom_registry_scope!(registry, || "a = 1".len());
+///
+///     len
+/// }
+///
+/// let registry = DoomGramRegistry::new();
+///
+/// load_config(&registry);
+/// doom_registry_scope!(DoomGramRegistry::global(), "startup", || ());
+///
+/// assert!(registry.names()[0].ends_with("::load_config"));
+/// assert_eq!(1, DoomGramRegistry::global().get("startup").unwrap().event_count());
+/// ```
+#[macro_export]
+macro_rules! doom_registry_scope {
+    ($registry:expr, $name:expr, $work:expr) => {
+        $crate::DoomGramRegistry::doom_scope($registry, $name, $work)
+    };
+    ($registry:expr, $work:expr) => {
+        $crate::DoomGramRegistry::doom_scope($registry, $crate::function_fully_qualified_name!(), $work)
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::DoomGramRegistry;
+
+    use crate::diagnostics::ManualClock;
+
+    use std::{
+        sync::Arc,
+        thread as std_thread,
+        time::Duration,
+    };
+
+
+    fn report_of(registry : &DoomGramRegistry) -> String {
+        let mut report = Vec::new();
+
+        registry.report(&mut report).unwrap();
+
+        String::from_utf8(report).unwrap()
+    }
+
+
+    #[test]
+    fn TEST_DoomGramRegistry_EMPTY() {
+        let registry = DoomGramRegistry::new();
+
+        assert!(registry.is_empty());
+        assert_eq!(0, registry.len());
+        assert_eq!(None, registry.get("x"));
+        assert_eq!("name  nmmm  strip\n", report_of(&registry));
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_REPORT_IS_SORTED_AND_ALIGNED() {
+        let registry = DoomGramRegistry::new();
+
+        registry.push_event_time_ns("zeta", 12);
+        registry.push_event_duration("alpha::beta::gamma", Duration::from_millis(250));
+        registry.push_event_time_ns("zeta", 40);
+        registry.with_gram("mu", |_| ());
+
+        let expected = "\
+name                nmmm              strip
+alpha::beta::gamma  1:250ms           ________a___
+mu                  0:                ____________
+zeta                2:12ns-26ns-40ns  _a__________
+";
+
+        assert_eq!(expected, report_of(&registry));
+        assert_eq!(vec!["alpha::beta::gamma", "mu", "zeta"], registry.names());
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_report_and_reset() {
+        let registry = DoomGramRegistry::new();
+
+        registry.push_event_time_ns("a", 5);
+
+        let mut report = Vec::new();
+
+        registry.report_and_reset(&mut report).unwrap();
+
+        assert_eq!("name  nmmm   strip\na     1:5ns  a___________\n", String::from_utf8(report).unwrap());
+
+        assert_eq!(1, registry.len());
+        assert_eq!(0, registry.get("a").unwrap().event_count());
+
+        registry.push_event_time_ns("a", 70);
+
+        assert_eq!("name  nmmm    strip\na     1:70ns  _a__________\n", report_of(&registry));
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_remove_AND_clear_AND_reset() {
+        let registry = DoomGramRegistry::new();
+
+        registry.push_event_time_ns("a", 1);
+        registry.push_event_time_ns("b", 2);
+
+        assert_eq!(Some(1), registry.remove("a").map(|dg| dg.event_count()));
+        assert_eq!(None, registry.remove("a"));
+
+        registry.reset();
+
+        assert_eq!(vec!["b"], registry.names());
+        assert_eq!(0, registry.get("b").unwrap().event_count());
+
+        registry.clear();
+
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_doom_scope() {
+        let registry = DoomGramRegistry::new();
+
+        let (r, t) = registry.doom_scope("sleep", || {
+            std_thread::sleep(Duration::from_millis(1));
+
+            17
+        });
+
+        assert_eq!(17, r);
+        assert!(t >= 1_000_000);
+        assert_eq!(Some(t), registry.get("sleep").unwrap().min_event_time());
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_doom_scope_with_clock() {
+        let registry = DoomGramRegistry::new();
+        let clock = ManualClock::new(0);
+
+        let (r, t) = registry.doom_scope_with_clock("work", &clock, || {
+            clock.advance_ns(2_500);
+
+            "done"
+        });
+
+        assert_eq!("done", r);
+        assert_eq!(2_500, t);
+        assert_eq!("1:2.500µs", registry.get("work").unwrap().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_doom_registry_scope_USES_FUNCTION_NAME() {
+        let registry = DoomGramRegistry::new();
+
+        let (r, _) = doom_registry_scope!(&registry, || 1 + 2);
+
+        assert_eq!(3, r);
+
+        doom_registry_scope!(&registry, "named", || ());
+
+        assert_eq!(
+            vec![
+                "diagnosticism::diagnostics::doomgram_registry::tests::TEST_doom_registry_scope_USES_FUNCTION_NAME",
+                "named",
+            ],
+            registry.names(),
+        );
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_global() {
+        let name = "TEST_DoomGramRegistry_global";
+
+        DoomGramRegistry::global().push_event_time_ns(name, 1);
+
+        assert!(std::ptr::eq(DoomGramRegistry::global(), DoomGramRegistry::global()));
+        assert_eq!(1, DoomGramRegistry::global().get(name).unwrap().event_count());
+    }
+
+    #[test]
+    fn TEST_DoomGramRegistry_MULTITHREADED() {
+        let registry = Arc::new(DoomGramRegistry::new());
+
+        let handles : Vec<_> = (0..4)
+            .map(|i| {
+                let registry = Arc::clone(&registry);
+
+                std_thread::spawn(move || {
+                    for _ in 0..1_000 {
+                        registry.push_event_time_ns(if 0 == i % 2 { "even" } else { "odd" }, 10);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(2_000, registry.get("even").unwrap().event_count());
+        assert_eq!(2_000, registry.get("odd").unwrap().event_count());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,418 @@
+// src/diagnostics/exemplar_doomgram.rs : `ExemplarDoomGram`
+
+use super::doomgram::DoomGram;
+
+use std::{
+    cmp as std_cmp,
+    collections::BinaryHeap,
+    time::Duration,
+};
+
+
+/// The maximum number of exemplars for which space is reserved on
+/// construction.
+const INITIAL_RESERVATION_MAX : usize = 64;
+
+
+/// An event retained by an [`ExemplarDoomGram`]: its time along with the
+/// tag supplied when it was pushed.
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub struct DoomExemplar<T> {
+    time_in_ns : u64,
+    tag :        T,
+}
+
+
+/// A [`DoomGram`] that additionally retains the slowest N events, each
+/// with a caller-supplied tag - such as a request id, or the location
+/// obtained from [`fileline!()`](crate::fileline) - so that the events
+/// behind a stray mark in the strip may be identified.
+///
+/// Memory use is bounded by the capacity given at construction: once it
+/// is reached, each new event displaces the fastest retained exemplar,
+/// and only if it is slower. Of events with equal times, the earliest
+/// pushed are retained.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::ExemplarDoomGram;
+///
+/// let mut edg = ExemplarDoomGram::new(2);
+///
+/// edg.push_event_time_ms(3, "req-1");
+/// edg.push_event_time_s(13, "req-2");
+/// edg.push_event_time_us(40, "req-3");
+/// edg.push_event_time_ms(250, "req-4");
+///
+/// assert_eq!("4:40µs-3.313s-13s", edg.as_doomgram().to_nmmm());
+///
+/// let slowest : Vec<_> = edg.exemplars().iter().map(|x| (x.time_in_ns(), *x.tag())).collect();
+///
+/// assert_eq!(vec![(13_000_000_000, "req-2"), (250_000_000, "req-4")], slowest);
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct ExemplarDoomGram<T> {
+    doomgram :   DoomGram,
+    capacity :   usize,
+    num_pushed : u64,
+    /// Arranged such that the top is the exemplar to be displaced next.
+    exemplars :  BinaryHeap<ExemplarEntry_<T>>,
+}
+
+
+#[derive(Clone)]
+#[derive(Debug)]
+struct ExemplarEntry_<T> {
+    exemplar : DoomExemplar<T>,
+    /// The order in which the event was pushed, by which ties are broken.
+    sequence : u64,
+}
+
+// API functions
+
+impl<T> ExemplarDoomGram<T> {
+    /// Creates a new, empty, instance that retains at most `capacity`
+    /// exemplars.
+    ///
+    /// A capacity of zero is permitted, in which case no exemplars are
+    /// retained.
+    pub fn new(capacity : usize) -> Self {
+        Self {
+            doomgram : DoomGram::default(),
+            capacity,
+            num_pushed : 0,
+            // the heap grows as required, so that a huge capacity does
+            // not reserve memory up front
+            exemplars : BinaryHeap::with_capacity(capacity.min(INITIAL_RESERVATION_MAX)),
+        }
+    }
+}
+
+// Mutating methods
+
+impl<T> ExemplarDoomGram<T> {
+    /// Clears the instance, resetting all values to the equivalent of a
+    /// newly constructed instance (with the same capacity).
+    pub fn clear(&mut self) {
+        self.doomgram.clear();
+        self.num_pushed = 0;
+        self.exemplars.clear();
+    }
+
+    /// Pushes an event with the given [`Duration`] and tag.
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &mut self,
+        duration : Duration,
+        tag : T,
+    ) -> bool {
+        self.push_event_time_ns(duration.as_nanos() as u64, tag)
+    }
+
+    /// Pushes an event with the given number of nanoseconds and tag.
+    ///
+    /// The event is recorded in the gram and, if it is among the slowest
+    /// pushed, retained as an exemplar. An event rejected by the gram (as
+    /// described for [`DoomGram::push_event_time_ns()`]) is not retained.
+    pub fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+        tag : T,
+    ) -> bool {
+        if !self.doomgram.push_event_time_ns(time_in_ns) {
+            return false;
+        }
+
+        self.push_exemplar_(time_in_ns, tag);
+
+        true
+    }
+
+    /// Pushes an event with the given number of microseconds and tag.
+    pub fn push_event_time_us(
+        &mut self,
+        time_in_us : u64,
+        tag : T,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * time_in_us, tag)
+    }
+
+    /// Pushes an event with the given number of milliseconds and tag.
+    pub fn push_event_time_ms(
+        &mut self,
+        time_in_ms : u64,
+        tag : T,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * time_in_ms, tag)
+    }
+
+    /// Pushes an event with the given number of seconds and tag.
+    pub fn push_event_time_s(
+        &mut self,
+        time_in_s : u64,
+        tag : T,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * 1_000 * time_in_s, tag)
+    }
+}
+
+// Non-mutating methods
+
+impl<T> DoomExemplar<T> {
+    /// The time of the event, in nanoseconds.
+    pub fn time_in_ns(&self) -> u64 {
+        self.time_in_ns
+    }
+
+    /// The tag supplied when the event was pushed.
+    pub fn tag(&self) -> &T {
+        &self.tag
+    }
+
+    /// Consumes the instance, returning its tag.
+    pub fn into_tag(self) -> T {
+        self.tag
+    }
+}
+
+impl<T> ExemplarDoomGram<T> {
+    /// The maximum number of exemplars retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Borrows the [`DoomGram`] of all events pushed.
+    pub fn as_doomgram(&self) -> &DoomGram {
+        &self.doomgram
+    }
+
+    /// Obtains a copy of the [`DoomGram`] of all events pushed.
+    pub fn to_doomgram(&self) -> DoomGram {
+        self.doomgram.clone()
+    }
+
+    /// Obtains the retained exemplars, slowest first (and, of those with
+    /// equal times, in the order in which they were pushed).
+    pub fn exemplars(&self) -> Vec<&DoomExemplar<T>> {
+        let mut entries : Vec<_> = self.exemplars.iter().collect();
+
+        // the greatest entry is the first to be displaced, so is last
+        entries.sort_unstable();
+
+        entries.into_iter().map(|entry| &entry.exemplar).collect()
+    }
+
+    /// Consumes the instance, returning the retained exemplars in the
+    /// order described for [`Self::exemplars()`].
+    pub fn into_exemplars(self) -> Vec<DoomExemplar<T>> {
+        let mut entries = self.exemplars.into_vec();
+
+        entries.sort_unstable();
+
+        entries.into_iter().map(|entry| entry.exemplar).collect()
+    }
+}
+
+// Implementation
+
+impl<T> ExemplarDoomGram<T> {
+    fn push_exemplar_(
+        &mut self,
+        time_in_ns : u64,
+        tag : T,
+    ) {
+        let sequence = self.num_pushed;
+
+        self.num_pushed += 1;
+
+        if 0 == self.capacity {
+            return;
+        }
+
+        if self.exemplars.len() == self.capacity {
+            match self.exemplars.peek() {
+                Some(fastest) if time_in_ns > fastest.exemplar.time_in_ns => {
+                    self.exemplars.pop();
+                },
+                _ => return,
+            }
+        }
+
+        self.exemplars.push(ExemplarEntry_ {
+            exemplar : DoomExemplar {
+                time_in_ns,
+                tag,
+            },
+            sequence,
+        });
+    }
+}
+
+// Trait implementations
+
+impl<T> Eq for ExemplarEntry_<T> {
+}
+
+
+impl<T> Ord for ExemplarEntry_<T> {
+    /// Orders by time descending and then by sequence ascending, so that
+    /// the greatest entry is the fastest, and latest, event.
+    fn cmp(
+        &self,
+        other : &Self,
+    ) -> std_cmp::Ordering {
+        other
+            .exemplar
+            .time_in_ns
+            .cmp(&self.exemplar.time_in_ns)
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+
+impl<T> PartialEq for ExemplarEntry_<T> {
+    fn eq(
+        &self,
+        other : &Self,
+    ) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+
+impl<T> PartialOrd for ExemplarEntry_<T> {
+    fn partial_cmp(
+        &self,
+        other : &Self,
+    ) -> Option<std_cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        DoomExemplar,
+        ExemplarDoomGram,
+    };
+
+    use crate::fileline;
+
+    use std::time::Duration;
+
+
+    fn times_and_tags<T : Copy>(edg : &ExemplarDoomGram<T>) -> Vec<(u64, T)> {
+        edg.exemplars().iter().map(|x| (x.time_in_ns(), *x.tag())).collect()
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_new() {
+        let edg = ExemplarDoomGram::<u32>::new(5);
+
+        assert_eq!(5, edg.capacity());
+        assert_eq!("0:", edg.as_doomgram().to_nmmm());
+        assert!(edg.exemplars().is_empty());
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_RETAINS_SLOWEST() {
+        let mut edg = ExemplarDoomGram::new(3);
+
+        for (i, ns) in [50, 10, 90, 30, 70, 20, 80].into_iter().enumerate() {
+            assert!(edg.push_event_time_ns(ns, i));
+        }
+
+        assert_eq!(7, edg.as_doomgram().event_count());
+        assert_eq!(vec![(90, 2), (80, 6), (70, 4)], times_and_tags(&edg));
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_TIES_RETAIN_EARLIEST() {
+        let mut edg = ExemplarDoomGram::new(2);
+
+        edg.push_event_time_us(5, 'a');
+        edg.push_event_time_us(5, 'b');
+        edg.push_event_time_us(5, 'c');
+        edg.push_event_time_us(1, 'd');
+
+        assert_eq!(vec![(5_000, 'a'), (5_000, 'b')], times_and_tags(&edg));
+
+        edg.push_event_time_us(6, 'e');
+
+        assert_eq!(vec![(6_000, 'e'), (5_000, 'a')], times_and_tags(&edg));
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_ZERO_CAPACITY() {
+        let mut edg = ExemplarDoomGram::new(0);
+
+        assert!(edg.push_event_time_s(10, ()));
+
+        assert_eq!(1, edg.as_doomgram().event_count());
+        assert!(edg.exemplars().is_empty());
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_OVERFLOW_NOT_RETAINED() {
+        let mut edg = ExemplarDoomGram::new(4);
+
+        assert!(edg.push_event_time_s(18_446_744_073, 1));
+        assert!(!edg.push_event_time_s(1, 2));
+
+        assert!(edg.as_doomgram().has_overflowed());
+        assert_eq!(vec![(18_446_744_073_000_000_000, 1)], times_and_tags(&edg));
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_fileline_TAGS() {
+        let mut edg = ExemplarDoomGram::new(1);
+
+        edg.push_event_duration(Duration::from_millis(2), fileline!());
+        edg.push_event_duration(Duration::from_secs(11), fileline!());
+
+        let exemplars = edg.clone().into_exemplars();
+
+        assert_eq!(1, exemplars.len());
+        assert_eq!(11_000_000_000, exemplars[0].time_in_ns());
+        assert!(exemplars[0].tag().starts_with("src/diagnostics/exemplar_doomgram.rs:"));
+
+        assert_eq!("______a___a_", edg.to_doomgram().to_strip());
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_HUGE_CAPACITY() {
+        let mut edg = ExemplarDoomGram::new(usize::MAX);
+
+        for ns in 1..=100 {
+            edg.push_event_time_ns(ns, ns);
+        }
+
+        assert_eq!(usize::MAX, edg.capacity());
+        assert_eq!(100, edg.exemplars().len());
+        assert_eq!((100, 100), (edg.exemplars()[0].time_in_ns(), *edg.exemplars()[0].tag()));
+    }
+
+    #[test]
+    fn TEST_ExemplarDoomGram_clear() {
+        let mut edg = ExemplarDoomGram::new(2);
+
+        edg.push_event_time_ms(1, String::from("x"));
+        edg.clear();
+
+        assert_eq!(2, edg.capacity());
+        assert_eq!(0, edg.as_doomgram().event_count());
+        assert_eq!(Vec::<DoomExemplar<String>>::new(), edg.into_exemplars());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
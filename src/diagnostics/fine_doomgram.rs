@@ -0,0 +1,652 @@
+// src/diagnostics/fine_doomgram.rs : `FineDoomGram`
+
+use super::{
+    doomgram::{
+        DoomGram,
+        DoomGramQuantile,
+        NUM_BUCKETS,
+    },
+    gram_utils,
+};
+
+use std::{
+    ops as std_ops,
+    time::Duration,
+};
+
+
+/// The maximum number of sub-buckets per decade supported by a
+/// [`FineDoomGram`].
+const MAX_SUB_BUCKETS_PER_DECADE : usize = 100;
+
+
+/// Decimal Order-Of-Magnitude frequency histoGRAM with a configurable
+/// number of (logarithmically spaced) sub-buckets per decade.
+///
+/// A [`DoomGram`] has one bucket per decade, so that, say, a move in
+/// latency from 2ms to 8ms is not visible in its strip. A [`FineDoomGram`]
+/// with, say, 2 sub-buckets per decade, separates the interval [1ms, 10ms)
+/// into [1ms, 3.162ms) and [3.162ms, 10ms).
+///
+/// It has the same push API, overflow rules, merging, and summary methods
+/// as [`DoomGram`] – which it maintains internally – with quantiles
+/// estimated within the (narrower) sub-buckets, and may be collapsed to a
+/// standard [`DoomGram`] via [`Self::as_doomgram()`] or
+/// [`Self::to_doomgram()`].
+///
+/// # Note:
+/// Because event times are integral numbers of nanoseconds, some
+/// sub-buckets in the lowest decades share a lower bound with their
+/// successor, and so can never receive events.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::FineDoomGram;
+///
+/// let mut fdg = FineDoomGram::new(2);
+///
+/// fdg.push_event_time_ms(2);
+/// fdg.push_event_time_ms(8);
+/// fdg.push_event_time_ms(8);
+///
+/// assert_eq!("3:2ms-6ms-8ms", fdg.to_nmmm());
+/// assert_eq!("____________aa__________", fdg.to_strip());
+/// assert_eq!("______a_____", fdg.to_doomgram().to_strip());
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub struct FineDoomGram {
+    doomgram :               DoomGram,
+    sub_buckets_per_decade : usize,
+    lower_bounds :           Vec<u64>,
+    buckets :                Vec<u64>,
+}
+
+// API functions
+
+impl FineDoomGram {
+    /// Creates a new, empty, instance with the given number of sub-buckets
+    /// per decade.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sub_buckets_per_decade` is zero or greater than 100.
+    pub fn new(sub_buckets_per_decade : usize) -> Self {
+        assert!(
+            (1..=MAX_SUB_BUCKETS_PER_DECADE).contains(&sub_buckets_per_decade),
+            "sub_buckets_per_decade must be in the range [1, {MAX_SUB_BUCKETS_PER_DECADE}], not {sub_buckets_per_decade}",
+        );
+
+        let lower_bounds = calc_lower_bounds_(sub_buckets_per_decade);
+        let buckets = vec![0; lower_bounds.len()];
+
+        Self {
+            doomgram : DoomGram::default(),
+            sub_buckets_per_decade,
+            lower_bounds,
+            buckets,
+        }
+    }
+}
+
+// Mutating methods
+
+impl FineDoomGram {
+    /// Clears the instance, resetting all values to the equivalent of a
+    /// newly constructed instance (with the same resolution).
+    pub fn clear(&mut self) {
+        self.doomgram.clear();
+        self.buckets.iter_mut().for_each(|n| *n = 0);
+    }
+
+    /// Merges the events recorded in `other` into this instance, as
+    /// described for [`DoomGram::merge()`], summing the sub-bucket counts.
+    ///
+    /// This is also available via the `+=` and `+` operators.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different number of sub-buckets per decade.
+    pub fn merge(
+        &mut self,
+        other : &FineDoomGram,
+    ) {
+        assert_eq!(
+            self.sub_buckets_per_decade, other.sub_buckets_per_decade,
+            "cannot merge instances with different numbers of sub-buckets per decade",
+        );
+
+        self.doomgram.merge(&other.doomgram);
+
+        for (count, &other_count) in self.buckets.iter_mut().zip(&other.buckets) {
+            *count = count.saturating_add(other_count);
+        }
+    }
+
+    /// Pushes an event with the given [`Duration`].
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &mut self,
+        duration : Duration,
+    ) -> bool {
+        self.push_event_time_ns(duration.as_nanos() as u64)
+    }
+
+    /// Pushes an event with the given number of nanoseconds.
+    pub fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        if self.doomgram.push_event_time_ns(time_in_ns) {
+            self.push_event_time_ns_(time_in_ns);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Pushes an event with the given number of microseconds.
+    pub fn push_event_time_us(
+        &mut self,
+        time_in_us : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * time_in_us)
+    }
+
+    /// Pushes an event with the given number of milliseconds.
+    pub fn push_event_time_ms(
+        &mut self,
+        time_in_ms : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * time_in_ms)
+    }
+
+    /// Pushes an event with the given number of seconds.
+    pub fn push_event_time_s(
+        &mut self,
+        time_in_s : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * 1_000 * time_in_s)
+    }
+}
+
+// Non-mutating methods
+
+impl FineDoomGram {
+    /// The number of sub-buckets per decade.
+    pub fn sub_buckets_per_decade(&self) -> usize {
+        self.sub_buckets_per_decade
+    }
+
+    /// The number of events counted in each sub-bucket, from the lowest
+    /// (starting at 1ns) to the highest (which is unbounded).
+    pub fn bucket_counts(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The inclusive lower bound, in nanoseconds, of each sub-bucket,
+    /// corresponding to [`Self::bucket_counts()`].
+    pub fn bucket_lower_bounds(&self) -> &[u64] {
+        &self.lower_bounds
+    }
+
+    /// Borrows the standard (one bucket per decade) [`DoomGram`]
+    /// equivalent of this instance.
+    pub fn as_doomgram(&self) -> &DoomGram {
+        &self.doomgram
+    }
+
+    /// Collapses this instance into a standard (one bucket per decade)
+    /// [`DoomGram`].
+    pub fn to_doomgram(&self) -> DoomGram {
+        self.doomgram.clone()
+    }
+
+    /// Number of events counted.
+    pub fn event_count(&self) -> usize {
+        self.doomgram.event_count()
+    }
+
+    /// See [`DoomGram::event_time_total()`].
+    pub fn event_time_total(&self) -> Option<u64> {
+        self.doomgram.event_time_total()
+    }
+
+    /// See [`DoomGram::event_time_total_raw()`].
+    pub fn event_time_total_raw(&self) -> u64 {
+        self.doomgram.event_time_total_raw()
+    }
+
+    /// Indicates whether overflow has occurred.
+    pub fn has_overflowed(&self) -> bool {
+        self.doomgram.has_overflowed()
+    }
+
+    pub fn min_event_time(&self) -> Option<u64> {
+        self.doomgram.min_event_time()
+    }
+
+    pub fn max_event_time(&self) -> Option<u64> {
+        self.doomgram.max_event_time()
+    }
+
+    /// See [`DoomGram::variance()`].
+    pub fn variance(&self) -> Option<f64> {
+        self.doomgram.variance()
+    }
+
+    /// See [`DoomGram::stddev()`].
+    pub fn stddev(&self) -> Option<f64> {
+        self.doomgram.stddev()
+    }
+
+    /// Estimates the `q`-quantile of the recorded event times (in
+    /// nanoseconds), as described for [`DoomGram::quantile()`], but
+    /// interpolated within, and bounded by, the sub-bucket containing the
+    /// requested rank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::FineDoomGram;
+    ///
+    /// let mut fdg = FineDoomGram::new(10);
+    ///
+    /// for us in 1..=100 {
+    ///     fdg.push_event_time_us(us);
+    /// }
+    ///
+    /// let p90 = fdg.quantile(0.9).unwrap();
+    ///
+    /// assert_eq!(79_433, p90.lower_bound());
+    /// assert_eq!(99_999, p90.upper_bound());
+    /// ```
+    pub fn quantile(
+        &self,
+        q : f64,
+    ) -> Option<DoomGramQuantile> {
+        self.doomgram.quantile_(q, &self.buckets, &self.lower_bounds)
+    }
+
+    /// See [`DoomGram::to_mmm()`].
+    pub fn to_mmm(&self) -> String {
+        self.doomgram.to_mmm()
+    }
+
+    /// See [`DoomGram::to_nmmm()`].
+    pub fn to_nmmm(&self) -> String {
+        self.doomgram.to_nmmm()
+    }
+
+    /// Returns an ASCII strip for the histogram, with one character per
+    /// sub-bucket, and so `12 * sub_buckets_per_decade()` characters in
+    /// total.
+    ///
+    /// The character encoding is as described for [`DoomGram::to_strip()`].
+    pub fn to_strip(&self) -> String {
+        gram_utils::buckets_to_strip(&self.buckets)
+    }
+}
+
+// Implementation
+
+impl FineDoomGram {
+    fn push_event_time_ns_(
+        &mut self,
+        time_in_ns : u64,
+    ) {
+        if let Some(decade) = gram_utils::doom_bucket_index(time_in_ns) {
+            let first = decade * self.sub_buckets_per_decade;
+            let bounds = &self.lower_bounds[first..first + self.sub_buckets_per_decade];
+
+            // the first bound of the decade is always <= `time_in_ns`
+            let index = first + bounds.partition_point(|&bound| bound <= time_in_ns) - 1;
+
+            self.buckets[index] += 1;
+        }
+    }
+}
+
+// Trait implementations
+
+impl From<&FineDoomGram> for DoomGram {
+    fn from(value : &FineDoomGram) -> Self {
+        value.to_doomgram()
+    }
+}
+
+
+impl std_ops::Add for FineDoomGram {
+    type Output = Self;
+
+    fn add(
+        mut self,
+        rhs : Self,
+    ) -> Self::Output {
+        self.merge(&rhs);
+
+        self
+    }
+}
+
+
+impl std_ops::Add<&FineDoomGram> for FineDoomGram {
+    type Output = Self;
+
+    fn add(
+        mut self,
+        rhs : &FineDoomGram,
+    ) -> Self::Output {
+        self.merge(rhs);
+
+        self
+    }
+}
+
+
+impl std_ops::AddAssign for FineDoomGram {
+    fn add_assign(
+        &mut self,
+        rhs : Self,
+    ) {
+        self.merge(&rhs);
+    }
+}
+
+
+impl std_ops::AddAssign<&FineDoomGram> for FineDoomGram {
+    fn add_assign(
+        &mut self,
+        rhs : &FineDoomGram,
+    ) {
+        self.merge(rhs);
+    }
+}
+
+
+// Helper functions
+
+/// Calculates the (integral, inclusive) lower bound of each sub-bucket,
+/// being the ceiling of `10^(decade + k / sub_buckets_per_decade)`.
+fn calc_lower_bounds_(sub_buckets_per_decade : usize) -> Vec<u64> {
+    let mut lower_bounds = Vec::with_capacity(NUM_BUCKETS * sub_buckets_per_decade);
+
+    for decade in 0..NUM_BUCKETS {
+        let decade_lower_bound = 10u64.pow(decade as u32);
+
+        lower_bounds.push(decade_lower_bound);
+
+        for k in 1..sub_buckets_per_decade {
+            let exponent = decade as f64 + k as f64 / sub_buckets_per_decade as f64;
+            let bound = 10f64.powf(exponent).ceil() as u64;
+
+            lower_bounds.push(bound.clamp(decade_lower_bound, 10 * decade_lower_bound - 1));
+        }
+    }
+
+    lower_bounds
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        DoomGram,
+        FineDoomGram,
+    };
+
+
+    #[test]
+    fn TEST_FineDoomGram_new() {
+        let fdg = FineDoomGram::new(5);
+
+        assert_eq!(5, fdg.sub_buckets_per_decade());
+        assert_eq!(60, fdg.bucket_counts().len());
+        assert_eq!(60, fdg.bucket_lower_bounds().len());
+
+        assert_eq!(0, fdg.event_count());
+        assert_eq!("", fdg.to_mmm());
+        assert_eq!("0:", fdg.to_nmmm());
+        assert_eq!("_".repeat(60), fdg.to_strip());
+    }
+
+    #[test]
+    #[should_panic]
+    fn TEST_FineDoomGram_new_WITH_ZERO() {
+        let _ = FineDoomGram::new(0);
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_bucket_lower_bounds() {
+        let fdg = FineDoomGram::new(2);
+
+        assert_eq!(
+            &[
+                1,
+                4,
+                10,
+                32,
+                100,
+                317,
+                1_000,
+                3_163,
+                10_000,
+                31_623,
+                100_000,
+                316_228,
+                1_000_000,
+                3_162_278,
+                10_000_000,
+                31_622_777,
+                100_000_000,
+                316_227_767,
+                1_000_000_000,
+                3_162_277_661,
+                10_000_000_000,
+                31_622_776_602,
+                100_000_000_000,
+                316_227_766_017,
+            ],
+            fdg.bucket_lower_bounds(),
+        );
+
+        for fdg in [FineDoomGram::new(3), FineDoomGram::new(5), FineDoomGram::new(10)] {
+            let bounds = fdg.bucket_lower_bounds();
+
+            assert!(bounds.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_SEPARATES_2MS_AND_8MS() {
+        let mut fdg = FineDoomGram::new(2);
+
+        fdg.push_event_time_ms(2);
+
+        let before = fdg.to_strip();
+
+        fdg.clear();
+        fdg.push_event_time_ms(8);
+
+        let after = fdg.to_strip();
+
+        assert_ne!(before, after);
+        assert_eq!(before.len(), after.len());
+
+        assert_eq!(fdg.to_doomgram().to_strip(), "______a_____");
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_WITH_ONE_SUB_BUCKET_MATCHES_DoomGram() {
+        let mut fdg = FineDoomGram::new(1);
+        let mut dg = DoomGram::default();
+
+        for ns in [0, 7, 11, 19, 7_000, 89_000, 248_000_000, 5_000_000_000, 309_000_000_000] {
+            assert_eq!(dg.push_event_time_ns(ns), fdg.push_event_time_ns(ns));
+        }
+
+        assert_eq!(dg.to_strip(), fdg.to_strip());
+        assert_eq!(dg.to_nmmm(), fdg.to_nmmm());
+        assert_eq!(dg, fdg.to_doomgram());
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_SUB_BUCKETS_SUM_TO_DECADES() {
+        for sub_buckets_per_decade in [2, 3, 5, 10] {
+            let mut fdg = FineDoomGram::new(sub_buckets_per_decade);
+
+            let mut ns = 1u64;
+
+            while ns < 10_000_000_000_000 {
+                fdg.push_event_time_ns(ns);
+
+                ns = ns * 7 / 3 + 1;
+            }
+
+            let dg = fdg.to_doomgram();
+            let decades = dg.buckets_();
+
+            for (decade, sub_buckets) in fdg.bucket_counts().chunks(sub_buckets_per_decade).enumerate() {
+                assert_eq!(decades[decade], sub_buckets.iter().sum::<u64>());
+            }
+        }
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_BOUNDARIES() {
+        let mut fdg = FineDoomGram::new(2);
+
+        fdg.push_event_time_ns(3_162_277);
+        fdg.push_event_time_ns(3_162_278);
+
+        assert_eq!(1, fdg.bucket_counts()[12]);
+        assert_eq!(1, fdg.bucket_counts()[13]);
+
+        // the last sub-bucket is unbounded
+        fdg.push_event_time_s(1_000_000);
+
+        assert_eq!(1, fdg.bucket_counts()[23]);
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_OVERFLOW() {
+        let mut fdg = FineDoomGram::new(10);
+
+        assert!(fdg.push_event_time_us(18_446_744_073_709_550));
+        assert!(fdg.push_event_time_us(1));
+        assert!(fdg.push_event_time_us(0));
+
+        assert!(!fdg.push_event_time_us(1));
+
+        assert!(fdg.has_overflowed());
+        assert_eq!(3, fdg.event_count());
+        assert_eq!(2, fdg.bucket_counts().iter().sum::<u64>());
+
+        assert_eq!("OVERFLOW", fdg.to_mmm());
+        assert_eq!("3:OVERFLOW", fdg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_merge_AND_Add() {
+        let mut fdg1 = FineDoomGram::new(2);
+        let mut fdg2 = FineDoomGram::new(2);
+        let mut all = FineDoomGram::new(2);
+
+        for ns in [0, 7, 2_000_000, 8_000_000] {
+            fdg1.push_event_time_ns(ns);
+            all.push_event_time_ns(ns);
+        }
+        for ns in [8_500_000, 40_000] {
+            fdg2.push_event_time_ns(ns);
+            all.push_event_time_ns(ns);
+        }
+
+        let mut merged = fdg1.clone();
+
+        merged.merge(&fdg2);
+
+        assert_eq!(all, merged);
+        assert_eq!(all, fdg1.clone() + &fdg2);
+        assert_eq!(all, fdg1.clone() + fdg2.clone());
+
+        let mut sum = fdg1.clone();
+
+        sum += &fdg2;
+        sum += FineDoomGram::new(2);
+
+        assert_eq!(all, sum);
+        assert_eq!("6:0s-3.090ms-8.500ms", sum.to_nmmm());
+        assert_eq!(fdg1.to_doomgram() + fdg2.to_doomgram(), sum.to_doomgram());
+    }
+
+    #[test]
+    #[should_panic(expected = "different numbers of sub-buckets per decade")]
+    fn TEST_FineDoomGram_merge_DIFFERENT_RESOLUTIONS() {
+        let mut fdg = FineDoomGram::new(2);
+
+        fdg.merge(&FineDoomGram::new(3));
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_quantile() {
+        let mut fdg = FineDoomGram::new(2);
+
+        assert_eq!(None, fdg.quantile(0.5));
+
+        for _ in 0..9 {
+            fdg.push_event_time_ms(2);
+        }
+        fdg.push_event_time_ms(8);
+
+        // the sub-buckets separate 2ms from 8ms, where the decade does not
+        let p50 = fdg.quantile(0.5).unwrap();
+        let p99 = fdg.quantile(0.99).unwrap();
+
+        assert_eq!((2_000_000, 3_162_277), (p50.lower_bound(), p50.upper_bound()));
+        assert_eq!((3_162_278, 8_000_000), (p99.lower_bound(), p99.upper_bound()));
+
+        let dg_p50 = fdg.to_doomgram().quantile(0.5).unwrap();
+
+        assert_eq!((2_000_000, 8_000_000), (dg_p50.lower_bound(), dg_p50.upper_bound()));
+
+        assert_eq!(None, fdg.quantile(1.5));
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_WITH_ONE_SUB_BUCKET_quantile_MATCHES_DoomGram() {
+        let mut fdg = FineDoomGram::new(1);
+
+        for ns in [0, 0, 7, 11, 19, 7_000, 89_000, 248_000_000, 5_000_000_000, 309_000_000_000] {
+            fdg.push_event_time_ns(ns);
+        }
+
+        for q in [0.0, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 1.0] {
+            assert_eq!(fdg.to_doomgram().quantile(q), fdg.quantile(q));
+        }
+    }
+
+    #[test]
+    fn TEST_FineDoomGram_variance_AND_stddev() {
+        let mut fdg = FineDoomGram::new(5);
+
+        assert_eq!(None, fdg.variance());
+        assert_eq!(None, fdg.stddev());
+
+        for ns in [2, 4, 4, 4, 5, 5, 7, 9] {
+            fdg.push_event_time_ns(ns);
+        }
+
+        assert_eq!(Some(4.0), fdg.variance());
+        assert_eq!(Some(2.0), fdg.stddev());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
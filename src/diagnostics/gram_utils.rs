@@ -0,0 +1,292 @@
+// src/diagnostics/gram_utils.rs : order-of-magnitude helpers shared by the grams
+
+use super::doomgram::NUM_BUCKETS;
+
+use std::{
+    fmt as std_fmt,
+    str as std_str,
+};
+
+
+/// The form of the min/mean/max summary of a gram that has overflowed.
+pub const OVERFLOW : &str = "OVERFLOW";
+
+
+/// The state common to the order-of-magnitude grams - the count, total,
+/// sum of squares, min, and max of the values recorded, whether the total
+/// has overflowed, and the decade bucket counts - on which
+/// [`DoomGram`](super::DoomGram) and [`OomGram`](super::OomGram) are
+/// built.
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct GramCore {
+    pub count :          usize,
+    pub total :          u64,
+    /// The sum of the squares of the values. Since the square of a sum is
+    /// no less than the sum of the squares, this cannot overflow unless
+    /// `total` does.
+    pub sum_sq :         u128,
+    pub has_overflowed : bool,
+    pub min :            Option<u64>,
+    pub max :            Option<u64>,
+    /// The number of values in each decade, from 1-9 to 1e11+. (Zero
+    /// values are counted, but not bucketed.)
+    pub buckets :        [u64; NUM_BUCKETS],
+}
+
+// Mutating methods
+
+impl GramCore {
+    /// Merges the values recorded in `other` into this instance.
+    ///
+    /// Counts, totals, sums of squares, and bucket counts are summed; min
+    /// and max are combined. The result has overflowed if either instance
+    /// has overflowed, or if summing the totals overflows, in which case,
+    /// as for [`Self::push()`], the total and sum of squares are left as
+    /// they were before the overflowing summation.
+    pub fn merge(
+        &mut self,
+        other : &GramCore,
+    ) {
+        match (
+            self.total.checked_add(other.total),
+            self.sum_sq.checked_add(other.sum_sq),
+        ) {
+            (Some(total), Some(sum_sq)) => {
+                self.total = total;
+                self.sum_sq = sum_sq;
+                self.has_overflowed |= other.has_overflowed;
+            },
+            _ => {
+                self.has_overflowed = true;
+            },
+        }
+
+        self.count = self.count.saturating_add(other.count);
+        self.min = combine_option(self.min, other.min, u64::min);
+        self.max = combine_option(self.max, other.max, u64::max);
+
+        for (count, other_count) in self.buckets.iter_mut().zip(other.buckets) {
+            *count = count.saturating_add(other_count);
+        }
+    }
+
+    /// Pushes a value.
+    ///
+    /// Returns `false`, and records nothing, if adding the value to the
+    /// total would overflow, whereupon the instance is marked as having
+    /// overflowed and ignores all subsequent values, the total and sum of
+    /// squares being left as they were before the overflowing addition.
+    pub fn push(
+        &mut self,
+        value : u64,
+    ) -> bool {
+        if self.has_overflowed {
+            return false;
+        }
+
+        let value_sq = u128::from(value) * u128::from(value);
+
+        match (self.total.checked_add(value), self.sum_sq.checked_add(value_sq)) {
+            (Some(total), Some(sum_sq)) => {
+                self.total = total;
+                self.sum_sq = sum_sq;
+                self.count += 1;
+                self.min = combine_option(self.min, Some(value), u64::min);
+                self.max = combine_option(self.max, Some(value), u64::max);
+
+                if let Some(index) = doom_bucket_index(value) {
+                    self.buckets[index] += 1;
+                }
+
+                true
+            },
+            _ => {
+                self.has_overflowed = true;
+
+                false
+            },
+        }
+    }
+}
+
+// Non-mutating methods
+
+impl GramCore {
+    /// Writes the min/mean/max summary to `w`, each value being written by
+    /// `write_value`: nothing if there are no values; [`OVERFLOW`] if the
+    /// total has overflowed; a single value if there is one, or if min and
+    /// max are equal; and otherwise `min-mean-max`.
+    pub fn write_mmm<W, F>(
+        &self,
+        w : &mut W,
+        mut write_value : F,
+    ) -> std_fmt::Result
+    where
+        W : std_fmt::Write + ?Sized,
+        F : FnMut(&mut W, u64) -> std_fmt::Result,
+    {
+        if 0 == self.count {
+            return Ok(());
+        }
+
+        if self.has_overflowed {
+            return w.write_str(OVERFLOW);
+        }
+
+        let min = self.min.unwrap();
+        let max = self.max.unwrap();
+
+        if 1 == self.count || min == max {
+            write_value(w, min)
+        } else {
+            let mean = self.total / self.count as u64;
+
+            write_value(w, min)?;
+            w.write_str("-")?;
+            write_value(w, mean)?;
+            w.write_str("-")?;
+            write_value(w, max)
+        }
+    }
+}
+
+
+pub fn calc_doom(v : u64) -> u32 {
+    if v >= 100_000_000 {
+        //    return count_decimal_digits(v);
+    } else {
+        if v >= 10_000 {
+            if v >= 1_000_000 {
+                if v >= 10_000_000 {
+                    return 8;
+                } else {
+                    return 7;
+                }
+            } else {
+                if v >= 100_000 {
+                    return 6;
+                } else {
+                    return 5;
+                }
+            }
+        } else {
+            if v >= 100 {
+                if v >= 1_000 {
+                    return 4;
+                } else {
+                    return 3;
+                }
+            } else {
+                if v >= 10 {
+                    return 2;
+                } else {
+                    if v > 0 {
+                        return 1;
+                    } else {
+                        return 0;
+                    }
+                }
+            }
+        }
+    }
+
+    if 0 == v {
+        0
+    } else {
+        let mut r = 0;
+        let mut v = v;
+
+        while 0 != v {
+            v /= 10;
+            r += 1;
+        }
+
+        r
+    }
+}
+
+/// Combines two optional values with `f`, or obtains whichever is present.
+pub fn combine_option(
+    lhs : Option<u64>,
+    rhs : Option<u64>,
+    f : fn(u64, u64) -> u64,
+) -> Option<u64> {
+    match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => Some(f(lhs, rhs)),
+        (lhs, None) => lhs,
+        (None, rhs) => rhs,
+    }
+}
+
+/// Obtains the index of the [`DoomGram`](super::DoomGram) bucket into
+/// which an event of the given number of nanoseconds falls, or `None` for
+/// a zero-time event (which is counted but not bucketed).
+pub fn doom_bucket_index(time_in_ns : u64) -> Option<usize> {
+    match calc_doom(time_in_ns) {
+        0 => None,
+        doom => Some(doom.min(NUM_BUCKETS as u32) as usize - 1),
+    }
+}
+
+pub fn gram_doom_to_char(
+    doom : u32,
+    ch_0 : u8,
+    ch_overflow : u8,
+    range : &[u8],
+) -> u8 {
+    if 0 == doom {
+        ch_0
+    } else {
+        if doom as usize > range.len() {
+            ch_overflow
+        } else {
+            range[doom as usize - 1]
+        }
+    }
+}
+
+/// Obtains the strip for the given bucket counts, with one character per
+/// bucket, as described for
+/// [`DoomGram::to_strip()`](super::DoomGram::to_strip).
+pub fn buckets_to_strip(buckets : &[u64]) -> String {
+    // TODO: this may need to be optimised, as costing around 20µs (2µs
+    // release) to process (and it's not helped by a faster
+    // `calc_doom()`):
+    //
+    // - initialise as `[ b'_', b'_', b'_', ... ]` and then don't to call `gram_doom_to_char()` unless count != 0;
+    // - T.B.D.
+
+    let strip : Vec<u8> = buckets.iter().map(|&count| bucket_count_to_char_(count)).collect();
+
+    // SAFETY: `strip` holds only `_`, `*`, or `a`–`z`. Each byte is
+    // written by `gram_doom_to_char()` as `ch_0`, `ch_overflow`, or an
+    // element of `range` (lowercase ASCII); all are valid UTF-8.
+    let s = unsafe { std_str::from_utf8_unchecked(&strip) };
+
+    s.into()
+}
+
+/// Writes the strip for the given bucket counts to `w`, as obtained from
+/// [`buckets_to_strip()`], without allocating.
+pub fn write_strip<W : std_fmt::Write + ?Sized>(
+    w : &mut W,
+    buckets : &[u64],
+) -> std_fmt::Result {
+    buckets
+        .iter()
+        .try_for_each(|&count| w.write_char(char::from(bucket_count_to_char_(count))))
+}
+
+fn bucket_count_to_char_(count : u64) -> u8 {
+    let ch_0 = b'_';
+    let ch_overflow = b'*';
+    let range = b"abcdefghijklmnopqrstuvwxyz";
+
+    gram_doom_to_char(calc_doom(count), ch_0, ch_overflow, range)
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -2,16 +2,72 @@
 
 use crate::macros::declare_and_publish;
 
+declare_and_publish!(atomic_doomgram, AtomicDoomGram);
+declare_and_publish!(clock, Clock, InstantClock, ManualClock);
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+pub use clock::{
+    ProcessCpuClock,
+    ThreadCpuClock,
+};
 declare_and_publish!(debug_squeezer, DebugSqueezer);
-declare_and_publish!(doomgram, DoomGram, doom_scope);
+declare_and_publish!(doom_future, doom_future, doom_future_with_clock, DoomFuture);
+declare_and_publish!(doom_guard, DoomGuard);
+declare_and_publish!(doom_target, DoomTarget);
+declare_and_publish!(doomgram, DoomGram, DoomGramQuantile, doom_scope, doom_scope_with_clock);
+declare_and_publish!(doomgram_chart, ChartScale, DoomGramBarChart);
+declare_and_publish!(
+    doomgram_parse,
+    parse_doomgram_mmm,
+    parse_doomgram_nmmm,
+    parse_doomgram_strip,
+    DoomGramSummary,
+    ParseDoomGramError,
+);
+declare_and_publish!(
+    doomgram_prometheus,
+    doomgram_to_prometheus,
+    write_doomgram_prometheus,
+    WritePrometheusError,
+);
+declare_and_publish!(doomgram_registry, DoomGramRegistry);
 declare_and_publish!(ellipsis, Ellipsis);
+declare_and_publish!(exemplar_doomgram, DoomExemplar, ExemplarDoomGram);
+declare_and_publish!(fine_doomgram, FineDoomGram);
 mod flf;
+mod gram_utils;
+declare_and_publish!(
+    oomgram,
+    BytesFormatter,
+    CountFormatter,
+    DurationFormatter,
+    OomFormatter,
+    OomGram,
+);
+declare_and_publish!(
+    outcome_doomgram,
+    doom_scope_result,
+    doom_scope_result_with_clock,
+    OutcomeDoomGram,
+    OutcomeDoomGuard,
+);
 declare_and_publish!(password, Password);
 declare_and_publish!(pub
     time_format,
+    DurationFormat,
+    DurationUnit,
+    FmtDuration,
+    FmtElapsed,
+    format_nanoseconds_into,
     NanosecondsStr,
     nanoseconds_to_string,
+    parse_nanoseconds,
+    ParseDurationFormatError,
+    ParseNanosecondsError,
+    try_nanoseconds_to_string,
+    write_nanoseconds,
 );
+declare_and_publish!(wide_doomgram, WideDoomGram);
+declare_and_publish!(windowed_doomgram, WindowedDoomGram);
 
 
 // ///////////////////////////// end of file //////////////////////////// //
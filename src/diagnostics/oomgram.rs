@@ -0,0 +1,468 @@
+// src/diagnostics/oomgram.rs : `OomGram` and value formatters
+
+use super::{
+    doomgram::{
+        DoomGram,
+        NUM_BUCKETS,
+    },
+    gram_utils::{
+        self,
+        GramCore,
+    },
+    time_format::nanoseconds_to_string,
+};
+
+
+/// Formats values recorded in an [`OomGram`], for its min/mean/max
+/// summaries.
+///
+/// Implemented by [`BytesFormatter`], [`CountFormatter`], and
+/// [`DurationFormatter`], and by any `Fn(u64) -> String`.
+pub trait OomFormatter {
+    /// Formats the given value.
+    fn format_value(
+        &self,
+        value : u64,
+    ) -> String;
+}
+
+
+/// [`OomFormatter`] for sizes in bytes, using binary unit prefixes, as in
+/// `"512B"`, `"1.5KiB"`, or `"9.76MiB"`.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct BytesFormatter;
+
+
+/// [`OomFormatter`] for plain counts, using decimal unit suffixes, as in
+/// `"999"`, `"1.23k"`, or `"1.5M"`.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct CountFormatter;
+
+
+/// [`OomFormatter`] for durations in nanoseconds, formatted by
+/// [`nanoseconds_to_string()`](crate::nanoseconds_to_string), as in
+/// `"7ns"`, `"773µs"`, or `"3ms"`.
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct DurationFormatter;
+
+
+/// Order-Of-Magnitude frequency histoGRAM for quantities of any unit, such
+/// as payload sizes, queue depths, or row counts.
+///
+/// Values are counted in the same twelve decade buckets - 1+, 10+, 100+,
+/// ..., 1e10+, 1e11+ - as [`DoomGram`] (which remains the time-specialised
+/// equivalent), and the strip is the same, while the min/mean/max
+/// summaries are formatted by an [`OomFormatter`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     BytesFormatter,
+///     OomGram,
+/// };
+///
+/// let mut og = OomGram::with_formatter(BytesFormatter);
+///
+/// og.push_value(200);
+/// og.push_value(1_536);
+/// og.push_value(3_000_000);
+///
+/// assert_eq!("3:200B-977KiB-2.86MiB", og.to_nmmm());
+/// assert_eq!("__aa__a_____", og.to_strip());
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct OomGram<F : OomFormatter = CountFormatter> {
+    formatter : F,
+    core :      GramCore,
+}
+
+// API functions
+
+impl OomGram {
+    /// Creates an empty instance that formats values as plain counts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<F : OomFormatter> OomGram<F> {
+    /// Creates an empty instance that formats values with the given
+    /// formatter.
+    pub fn with_formatter(formatter : F) -> Self {
+        Self {
+            formatter,
+            core : GramCore::default(),
+        }
+    }
+}
+
+// Mutating methods
+
+impl<F : OomFormatter> OomGram<F> {
+    /// Clears the instance, resetting all values (other than the formatter)
+    /// to the equivalent of a newly constructed instance.
+    pub fn clear(&mut self) {
+        self.core = GramCore::default();
+    }
+
+    /// Merges the values recorded in `other` into this instance, as
+    /// described for [`DoomGram::merge()`].
+    pub fn merge<G : OomFormatter>(
+        &mut self,
+        other : &OomGram<G>,
+    ) {
+        self.core.merge(&other.core);
+    }
+
+    /// Pushes a value.
+    ///
+    /// Returns `false`, and records nothing, if adding the value to the
+    /// total would overflow, whereupon the instance is marked as having
+    /// overflowed and ignores all subsequent values.
+    pub fn push_value(
+        &mut self,
+        value : u64,
+    ) -> bool {
+        self.core.push(value)
+    }
+}
+
+// Non-mutating methods
+
+impl<F : OomFormatter> OomGram<F> {
+    /// The formatter.
+    pub fn formatter(&self) -> &F {
+        &self.formatter
+    }
+
+    /// The number of values recorded.
+    pub fn value_count(&self) -> usize {
+        self.core.count
+    }
+
+    /// The sum of the values recorded, or `None` if the total has
+    /// overflowed.
+    pub fn value_total(&self) -> Option<u64> {
+        if self.core.has_overflowed {
+            None
+        } else {
+            Some(self.core.total)
+        }
+    }
+
+    /// The sum of the values recorded, regardless of whether the total
+    /// has overflowed, in which case it is the total before the push or
+    /// merge that overflowed.
+    pub fn value_total_raw(&self) -> u64 {
+        self.core.total
+    }
+
+    /// Indicates whether the total has overflowed.
+    pub fn has_overflowed(&self) -> bool {
+        self.core.has_overflowed
+    }
+
+    /// The smallest value recorded, or `None` if there are none.
+    pub fn min_value(&self) -> Option<u64> {
+        self.core.min
+    }
+
+    /// The largest value recorded, or `None` if there are none.
+    pub fn max_value(&self) -> Option<u64> {
+        self.core.max
+    }
+
+    /// The number of values in each of the twelve decade buckets, from
+    /// 1-9 to 1e11+. (Zero values are counted, but not bucketed.)
+    pub fn bucket_counts(&self) -> [u64; NUM_BUCKETS] {
+        self.core.buckets
+    }
+
+    /// Returns min, mean, and max values, each formatted by the formatter,
+    /// in the same form as [`DoomGram::to_mmm()`].
+    pub fn to_mmm(&self) -> String {
+        use std::fmt::Write as _;
+
+        let mut s = String::new();
+
+        // NOTE: writing to a `String` cannot fail
+        let _ = self.core.write_mmm(&mut s, |w, value| w.write_str(&self.formatter.format_value(value)));
+
+        s
+    }
+
+    /// Like [`Self::to_mmm()`], prefixed with the value count and `:`, in
+    /// the same form as [`DoomGram::to_nmmm()`].
+    pub fn to_nmmm(&self) -> String {
+        format!("{}:{}", self.core.count, self.to_mmm())
+    }
+
+    /// Returns an ASCII strip for the histogram, with one character per
+    /// decade bucket, as described for [`DoomGram::to_strip()`].
+    pub fn to_strip(&self) -> String {
+        gram_utils::buckets_to_strip(&self.core.buckets)
+    }
+}
+
+// Trait implementations
+
+impl<F : Fn(u64) -> String> OomFormatter for F {
+    fn format_value(
+        &self,
+        value : u64,
+    ) -> String {
+        self(value)
+    }
+}
+
+
+impl OomFormatter for BytesFormatter {
+    fn format_value(
+        &self,
+        value : u64,
+    ) -> String {
+        format_scaled_(value, 1_024, &["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"])
+    }
+}
+
+
+impl OomFormatter for CountFormatter {
+    fn format_value(
+        &self,
+        value : u64,
+    ) -> String {
+        format_scaled_(value, 1_000, &["", "k", "M", "G", "T", "P", "E"])
+    }
+}
+
+
+impl OomFormatter for DurationFormatter {
+    fn format_value(
+        &self,
+        value : u64,
+    ) -> String {
+        nanoseconds_to_string(i64::try_from(value).unwrap_or(i64::MAX), "").into()
+    }
+}
+
+
+impl From<&DoomGram> for OomGram<DurationFormatter> {
+    fn from(dg : &DoomGram) -> Self {
+        Self {
+            formatter : DurationFormatter,
+            core : dg.core_().clone(),
+        }
+    }
+}
+
+
+// Helper functions
+
+/// Formats `value` in the largest unit not exceeding it, truncated to
+/// three significant digits, with trailing fractional zeros removed.
+fn format_scaled_(
+    value : u64,
+    base : u64,
+    units : &[&str],
+) -> String {
+    let mut divisor = 1u128;
+    let mut unit_index = 0;
+
+    while unit_index + 1 < units.len() && u128::from(value) >= divisor * u128::from(base) {
+        divisor *= u128::from(base);
+        unit_index += 1;
+    }
+
+    let unit = units[unit_index];
+    let whole = u128::from(value) / divisor;
+
+    if 0 == unit_index || whole >= 100 {
+        return format!("{whole}{unit}");
+    }
+
+    let num_frac_digits = if whole >= 10 { 1 } else { 2 };
+    let frac_scale = 10u128.pow(num_frac_digits);
+    let frac = (u128::from(value) % divisor) * frac_scale / divisor;
+
+    let frac = format!("{frac:0width$}", width = num_frac_digits as usize);
+    let frac = frac.trim_end_matches('0');
+
+    if frac.is_empty() {
+        format!("{whole}{unit}")
+    } else {
+        format!("{whole}.{frac}{unit}")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        BytesFormatter,
+        CountFormatter,
+        DurationFormatter,
+        OomFormatter,
+        OomGram,
+    };
+
+    use crate::diagnostics::DoomGram;
+
+
+    #[test]
+    fn TEST_BytesFormatter() {
+        #[rustfmt::skip]
+        let cases = [
+            (  0,                         "0B"),
+            (  1_023,                     "1023B"),
+            (  1_024,                     "1KiB"),
+            (  1_536,                     "1.5KiB"),
+            (  10_000,                    "9.76KiB"),
+            (  102_400,                   "100KiB"),
+            (  1_048_575,                 "1023KiB"),
+            (  1_048_576,                 "1MiB"),
+            (  3_000_000,                 "2.86MiB"),
+            (  5 * (1 << 30),             "5GiB"),
+            (  u64::MAX,                  "15.9EiB"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(expected, BytesFormatter.format_value(value), "formatting {value}");
+        }
+    }
+
+    #[test]
+    fn TEST_CountFormatter() {
+        #[rustfmt::skip]
+        let cases = [
+            (  0,                         "0"),
+            (  999,                       "999"),
+            (  1_000,                     "1k"),
+            (  1_234,                     "1.23k"),
+            (  12_345,                    "12.3k"),
+            (  123_456,                   "123k"),
+            (  1_500_000,                 "1.5M"),
+            (  7_000_000_000,             "7G"),
+            (  u64::MAX,                  "18.4E"),
+        ];
+
+        for (value, expected) in cases {
+            assert_eq!(expected, CountFormatter.format_value(value), "formatting {value}");
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormatter() {
+        assert_eq!("7ns", DurationFormatter.format_value(7));
+        assert_eq!("123.4ms", DurationFormatter.format_value(123_456_789));
+    }
+
+    #[test]
+    fn TEST_OomGram_Default() {
+        let og = OomGram::new();
+
+        assert_eq!(0, og.value_count());
+        assert_eq!(Some(0), og.value_total());
+        assert!(!og.has_overflowed());
+        assert_eq!(None, og.min_value());
+        assert_eq!(None, og.max_value());
+        assert_eq!("", og.to_mmm());
+        assert_eq!("0:", og.to_nmmm());
+        assert_eq!("____________", og.to_strip());
+    }
+
+    #[test]
+    fn TEST_OomGram_COUNTS() {
+        let mut og = OomGram::new();
+
+        for depth in [0, 3, 3, 40, 2_500] {
+            assert!(og.push_value(depth));
+        }
+
+        assert_eq!(5, og.value_count());
+        assert_eq!(Some(2_546), og.value_total());
+        assert_eq!([2, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0], og.bucket_counts());
+        assert_eq!("0-509-2.5k", og.to_mmm());
+        assert_eq!("5:0-509-2.5k", og.to_nmmm());
+        assert_eq!("aa_a________", og.to_strip());
+    }
+
+    #[test]
+    fn TEST_OomGram_CUSTOM_FORMATTER() {
+        let mut og = OomGram::with_formatter(|rows : u64| format!("{rows} rows"));
+
+        og.push_value(12);
+
+        assert_eq!("1:12 rows", og.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_OomGram_OVERFLOW() {
+        let mut og = OomGram::with_formatter(BytesFormatter);
+
+        assert!(og.push_value(u64::MAX - 1));
+        assert!(!og.push_value(2));
+        assert!(!og.push_value(0));
+
+        assert!(og.has_overflowed());
+        assert_eq!(1, og.value_count());
+        assert_eq!(None, og.value_total());
+        assert_eq!(u64::MAX - 1, og.value_total_raw());
+        assert_eq!("OVERFLOW", og.to_mmm());
+        assert_eq!("1:OVERFLOW", og.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_OomGram_merge_AND_clear() {
+        let mut og1 = OomGram::new();
+        let mut og2 = OomGram::with_formatter(BytesFormatter);
+
+        og1.push_value(5);
+        og2.push_value(500);
+        og2.push_value(50_000);
+
+        og1.merge(&og2);
+
+        assert_eq!("3:5-16.8k-50k", og1.to_nmmm());
+        assert_eq!("a_a_a_______", og1.to_strip());
+
+        og1.clear();
+
+        assert_eq!(OomGram::new(), og1);
+    }
+
+    #[test]
+    fn TEST_OomGram_FROM_DoomGram() {
+        let mut dg = DoomGram::default();
+
+        dg.push_event_time_ns(7);
+        dg.push_event_time_us(92);
+        dg.push_event_time_ms(3);
+        dg.push_event_time_ns(11);
+
+        let og = OomGram::from(&dg);
+
+        assert_eq!(dg.to_nmmm(), og.to_nmmm());
+        assert_eq!(dg.to_strip(), og.to_strip());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
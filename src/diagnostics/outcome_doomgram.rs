@@ -0,0 +1,428 @@
+// src/diagnostics/outcome_doomgram.rs : `OutcomeDoomGram`
+
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doom_guard::GuardTimer_,
+    doomgram::DoomGram,
+};
+
+
+/// A pair of [`DoomGram`]s that separately record the times of events that
+/// succeed and of those that fail, so that (for example) fast failures do
+/// not obscure slow successes.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     doom_scope_result,
+///     OutcomeDoomGram,
+/// };
+///
+/// let mut odg = OutcomeDoomGram::default();
+///
+/// for s in ["1", "2", "three"] {
+///     let _ = doom_scope_result(&mut odg, || s.parse::<i32>());
+/// }
+///
+/// assert_eq!(2, odg.ok().event_count());
+/// assert_eq!(1, odg.err().event_count());
+/// assert!(odg.to_nmmm().starts_with("ok=2:"));
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct OutcomeDoomGram {
+    ok :  DoomGram,
+    err : DoomGram,
+}
+
+
+/// Scope guard that records the time elapsed since its creation into
+/// either the success or the failure gram of an [`OutcomeDoomGram`].
+///
+/// Obtained from [`OutcomeDoomGram::time_scope()`]. The outcome is
+/// specified by [`Self::succeed()`], [`Self::fail()`], or
+/// [`Self::complete()`]; a guard that is dropped without any of these
+/// having been called - as occurs when a `?` returns early - records into
+/// the failure gram.
+#[must_use = "the elapsed time is recorded when the guard is dropped"]
+pub struct OutcomeDoomGuard<'a, C : Clock = InstantClock> {
+    odg :   &'a mut OutcomeDoomGram,
+    timer : GuardTimer_<C>,
+}
+
+// API functions
+
+impl<'a, C : Clock> OutcomeDoomGuard<'a, C> {
+    fn new_(
+        odg : &'a mut OutcomeDoomGram,
+        clock : C,
+    ) -> Self {
+        Self {
+            odg,
+            timer : GuardTimer_::new_(clock),
+        }
+    }
+}
+
+// Mutating methods
+
+impl OutcomeDoomGram {
+    /// Clears both grams.
+    pub fn clear(&mut self) {
+        self.ok.clear();
+        self.err.clear();
+    }
+
+    /// Mutable access to the gram of events that succeeded.
+    pub fn ok_mut(&mut self) -> &mut DoomGram {
+        &mut self.ok
+    }
+
+    /// Mutable access to the gram of events that failed.
+    pub fn err_mut(&mut self) -> &mut DoomGram {
+        &mut self.err
+    }
+
+    /// Pushes an event with the given number of nanoseconds into the
+    /// success gram if `succeeded` is `true`, or into the failure gram
+    /// otherwise.
+    pub fn push_event_time_ns(
+        &mut self,
+        succeeded : bool,
+        time_in_ns : u64,
+    ) -> bool {
+        if succeeded {
+            self.ok.push_event_time_ns(time_in_ns)
+        } else {
+            self.err.push_event_time_ns(time_in_ns)
+        }
+    }
+
+    /// Creates an [`OutcomeDoomGuard`] that records the time elapsed from
+    /// now until its outcome is specified (or it is dropped).
+    pub fn time_scope(&mut self) -> OutcomeDoomGuard<'_> {
+        OutcomeDoomGuard::new_(self, InstantClock)
+    }
+
+    /// Creates an [`OutcomeDoomGuard`] that records the time elapsed, as
+    /// measured by the given [`Clock`], from now until its outcome is
+    /// specified (or it is dropped).
+    pub fn time_scope_with_clock<C : Clock>(
+        &mut self,
+        clock : C,
+    ) -> OutcomeDoomGuard<'_, C> {
+        OutcomeDoomGuard::new_(self, clock)
+    }
+}
+
+impl<C : Clock> OutcomeDoomGuard<'_, C> {
+    /// Discards the measurement, such that nothing is recorded.
+    pub fn cancel(mut self) {
+        self.timer.cancel_();
+    }
+
+    /// Records the elapsed time into the success gram, and returns it (in
+    /// nanoseconds).
+    pub fn succeed(mut self) -> u64 {
+        self.record_(true)
+    }
+
+    /// Records the elapsed time into the failure gram, and returns it (in
+    /// nanoseconds).
+    pub fn fail(mut self) -> u64 {
+        self.record_(false)
+    }
+
+    /// Records the elapsed time into the success or failure gram according
+    /// to whether `result` is `Ok` or `Err`, and returns it (in
+    /// nanoseconds).
+    pub fn complete<T, E>(
+        mut self,
+        result : &Result<T, E>,
+    ) -> u64 {
+        self.record_(result.is_ok())
+    }
+}
+
+// Non-mutating methods
+
+impl OutcomeDoomGram {
+    /// The gram of events that succeeded.
+    pub fn ok(&self) -> &DoomGram {
+        &self.ok
+    }
+
+    /// The gram of events that failed.
+    pub fn err(&self) -> &DoomGram {
+        &self.err
+    }
+
+    /// The total number of events, successful and failed.
+    pub fn event_count(&self) -> usize {
+        self.ok.event_count() + self.err.event_count()
+    }
+
+    /// Obtains a combined summary of the form `"ok=<nmmm>/err=<nmmm>"`, in
+    /// which each part is as obtained from [`DoomGram::to_nmmm()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::OutcomeDoomGram;
+    ///
+    /// let mut odg = OutcomeDoomGram::default();
+    ///
+    /// odg.push_event_time_ns(true, 3_000_000);
+    /// odg.push_event_time_ns(true, 5_000_000);
+    /// odg.push_event_time_ns(false, 7_000);
+    ///
+    /// assert_eq!("ok=2:3ms-4ms-5ms/err=1:7µs", odg.to_nmmm());
+    /// ```
+    pub fn to_nmmm(&self) -> String {
+        format!("ok={}/err={}", self.ok.to_nmmm(), self.err.to_nmmm())
+    }
+}
+
+impl<C : Clock> OutcomeDoomGuard<'_, C> {
+    /// The time elapsed (in nanoseconds) since the guard was created.
+    pub fn elapsed_ns(&self) -> u64 {
+        self.timer.elapsed_ns_()
+    }
+}
+
+// Implementation
+
+impl<C : Clock> OutcomeDoomGuard<'_, C> {
+    fn record_(
+        &mut self,
+        succeeded : bool,
+    ) -> u64 {
+        self.timer.record_(|time_in_ns| {
+            self.odg.push_event_time_ns(succeeded, time_in_ns);
+        })
+    }
+}
+
+// Trait implementations
+
+impl<C : Clock> Drop for OutcomeDoomGuard<'_, C> {
+    fn drop(&mut self) {
+        if self.timer.is_recording_() {
+            self.record_(false);
+        }
+    }
+}
+
+
+/// Executes a fallible closure, records its elapsed time in the success or
+/// failure gram of an [`OutcomeDoomGram`] according to its result, and
+/// returns the closure's result together with the measured elapsed time
+/// (in nanoseconds).
+pub fn doom_scope_result<F, T, E>(
+    odg : &mut OutcomeDoomGram,
+    work : F,
+) -> (
+    Result<T, E>, // work_result
+    u64,          // measured_elapsed_time_in_ns
+)
+where
+    F : FnOnce() -> Result<T, E>,
+{
+    doom_scope_result_with_clock(odg, InstantClock, work)
+}
+
+/// Executes a fallible closure, records its elapsed time, as measured by
+/// the given [`Clock`], in the success or failure gram of an
+/// [`OutcomeDoomGram`] according to its result, and returns the closure's
+/// result together with the measured elapsed time (in nanoseconds).
+pub fn doom_scope_result_with_clock<C, F, T, E>(
+    odg : &mut OutcomeDoomGram,
+    clock : C,
+    work : F,
+) -> (
+    Result<T, E>, // work_result
+    u64,          // measured_elapsed_time_in_ns
+)
+where
+    C : Clock,
+    F : FnOnce() -> Result<T, E>,
+{
+    let before = clock.now_ns();
+
+    let work_result = work();
+
+    let after = clock.now_ns();
+
+    let measured_elapsed_time_in_ns = after.saturating_sub(before);
+
+    odg.push_event_time_ns(work_result.is_ok(), measured_elapsed_time_in_ns);
+
+    (
+        work_result,
+        measured_elapsed_time_in_ns,
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        doom_scope_result,
+        doom_scope_result_with_clock,
+        OutcomeDoomGram,
+    };
+
+    use crate::diagnostics::ManualClock;
+
+
+    #[test]
+    fn TEST_OutcomeDoomGram_Default() {
+        let odg = OutcomeDoomGram::default();
+
+        assert_eq!(0, odg.event_count());
+        assert_eq!("ok=0:/err=0:", odg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_doom_scope_result_ROUTES_BY_OUTCOME() {
+        let mut odg = OutcomeDoomGram::default();
+
+        let (r, _) = doom_scope_result(&mut odg, || "12".parse::<i32>());
+
+        assert_eq!(Ok(12), r);
+
+        let (r, _) = doom_scope_result(&mut odg, || "x".parse::<i32>());
+
+        assert!(r.is_err());
+
+        let (r, _) = doom_scope_result(&mut odg, || "34".parse::<i32>());
+
+        assert_eq!(Ok(34), r);
+
+        assert_eq!(2, odg.ok().event_count());
+        assert_eq!(1, odg.err().event_count());
+        assert_eq!(3, odg.event_count());
+    }
+
+    #[test]
+    fn TEST_doom_scope_result_with_clock() {
+        let clock = ManualClock::new(0);
+        let mut odg = OutcomeDoomGram::default();
+
+        let (_, t) = doom_scope_result_with_clock(&mut odg, &clock, || {
+            clock.advance_ns(250_000_000);
+
+            Ok::<_, ()>(())
+        });
+
+        assert_eq!(250_000_000, t);
+
+        let (r, _) = doom_scope_result_with_clock(&mut odg, &clock, || {
+            clock.advance_ns(3_000);
+
+            Err::<(), _>("failed")
+        });
+
+        assert_eq!(Err("failed"), r);
+
+        assert_eq!("ok=1:250ms/err=1:3µs", odg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_OutcomeDoomGuard_OUTCOMES() {
+        let clock = ManualClock::new(0);
+        let mut odg = OutcomeDoomGram::default();
+
+        {
+            let guard = odg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(10);
+
+            assert_eq!(10, guard.succeed());
+        }
+
+        {
+            let guard = odg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(20);
+
+            assert_eq!(20, guard.fail());
+        }
+
+        {
+            let guard = odg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(30);
+
+            assert_eq!(30, guard.complete(&Ok::<_, ()>(0)));
+        }
+
+        {
+            let guard = odg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(40);
+
+            assert_eq!(40, guard.complete(&Err::<(), _>(0)));
+        }
+
+        {
+            let guard = odg.time_scope_with_clock(&clock);
+
+            clock.advance_ns(50);
+
+            guard.cancel();
+        }
+
+        assert_eq!("ok=2:10ns-20ns-30ns/err=2:20ns-30ns-40ns", odg.to_nmmm());
+    }
+
+    #[test]
+    fn TEST_OutcomeDoomGuard_DROP_RECORDS_FAILURE() {
+        fn f(
+            odg : &mut OutcomeDoomGram,
+            s : &str,
+        ) -> Result<i32, std::num::ParseIntError> {
+            let guard = odg.time_scope();
+
+            let v = s.parse::<i32>()?;
+
+            guard.succeed();
+
+            Ok(v)
+        }
+
+        let mut odg = OutcomeDoomGram::default();
+
+        assert!(f(&mut odg, "1").is_ok());
+        assert!(f(&mut odg, "one").is_err());
+        assert!(f(&mut odg, "two").is_err());
+
+        assert_eq!(1, odg.ok().event_count());
+        assert_eq!(2, odg.err().event_count());
+    }
+
+    #[test]
+    fn TEST_OutcomeDoomGram_clear() {
+        let mut odg = OutcomeDoomGram::default();
+
+        odg.ok_mut().push_event_time_ns(1);
+        odg.err_mut().push_event_time_ns(2);
+
+        assert_eq!(2, odg.event_count());
+
+        odg.clear();
+
+        assert_eq!(OutcomeDoomGram::default(), odg);
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,920 @@
+// src/diagnostics/time_format/duration_format.rs : `DurationFormat`
+
+use super::{
+    format::{
+        write_adaptive,
+        write_bytes,
+        write_compound,
+        write_u64,
+        COMPOUND_UNITS,
+        SUFFIXES,
+        SUFFIXES_ASCII,
+    },
+    nanoseconds_str::NanosecondsStr,
+};
+
+use std::{
+    error as std_error,
+    fmt as std_fmt,
+    str as std_str,
+};
+
+
+/// Large enough for the widest permitted output, before padding: a sign,
+/// 20 digits of whole, a point, up to 18 decimals, and the suffix; and
+/// after padding, [`DurationFormat::MAX_LEN`].
+const BUFFER_SIZE : usize = 72;
+
+/// The unit suffixes recognised by [`DurationFormat::from_spec()`].
+const UNIT_SUFFIXES : [(&str, DurationUnit); 6] = [
+    ("ns", DurationUnit::Nanoseconds),
+    ("µs", DurationUnit::Microseconds),
+    ("μs", DurationUnit::Microseconds),
+    ("us", DurationUnit::Microseconds),
+    ("ms", DurationUnit::Milliseconds),
+    ("s", DurationUnit::Seconds),
+];
+
+
+/// The unit of a [`DurationFormat`].
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum DurationUnit {
+    /// Nanoseconds, with the suffix `ns`.
+    Nanoseconds,
+    /// Microseconds, with the suffix `µs`.
+    Microseconds,
+    /// Milliseconds, with the suffix `ms`.
+    Milliseconds,
+    /// Seconds, with the suffix `s`.
+    Seconds,
+}
+
+
+/// Options for formatting a nanosecond count as a duration string,
+/// extending the adaptive formatting of
+/// [`nanoseconds_to_string()`](crate::nanoseconds_to_string) with a fixed
+/// unit, a fixed precision, and right-alignment to a width, as required
+/// for tabular reports, and with a compound form - such as `"2m 5.3s"`
+/// or `"1d 4h"` - for long durations.
+///
+/// An instance may be built by method chaining from [`Self::new()`], or
+/// obtained from a format specification by [`Self::from_spec()`] (or
+/// [`str::parse()`]).
+///
+/// Digits beyond the requested precision are truncated, as they are by
+/// the adaptive formatting.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     DurationFormat,
+///     DurationUnit,
+/// };
+///
+/// let df = DurationFormat::new().unit(DurationUnit::Milliseconds).decimals(3).width(10);
+///
+/// assert_eq!("  12.345ms", df.format(12_345_678));
+/// assert_eq!("   0.004ms", df.format(4_321));
+/// assert_eq!(df, DurationFormat::from_spec("10.3ms").unwrap());
+/// ```
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Default)]
+#[derive(Eq, PartialEq)]
+pub struct DurationFormat {
+    plus_sign : bool,
+    compound :  bool,
+    ascii :     bool,
+    width :     usize,
+    unit :      Option<DurationUnit>,
+    precision : Option<Precision_>,
+}
+
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+enum Precision_ {
+    Decimals(usize),
+    SignificantDigits(usize),
+}
+
+
+/// Error type returned by [`DurationFormat::from_spec()`].
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum ParseDurationFormatError {
+    /// A character that is not permitted at its position in the
+    /// specification, at the given byte offset.
+    UnexpectedCharacter {
+        position :  usize,
+        character : char,
+    },
+    /// A `.` or `~` is not followed by a number.
+    MissingPrecision,
+    /// The width is greater than [`DurationFormat::MAX_WIDTH`].
+    WidthOutOfRange,
+    /// The number of decimals is greater than
+    /// [`DurationFormat::MAX_PRECISION`], or the number of significant
+    /// digits is 0 or greater than [`DurationFormat::MAX_PRECISION`].
+    PrecisionOutOfRange,
+}
+
+// Trait implementations
+
+impl std_fmt::Display for ParseDurationFormatError {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        match self {
+            Self::UnexpectedCharacter {
+                position,
+                character,
+            } => write!(f, "unexpected character {character:?} at position {position}"),
+            Self::MissingPrecision => f.write_str("missing precision after '.' or '~'"),
+            Self::WidthOutOfRange => f.write_str("width out of range"),
+            Self::PrecisionOutOfRange => f.write_str("precision out of range"),
+        }
+    }
+}
+
+
+impl std_error::Error for ParseDurationFormatError {}
+
+
+impl std_str::FromStr for DurationFormat {
+    type Err = ParseDurationFormatError;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        Self::from_spec(s)
+    }
+}
+
+
+// API functions
+
+impl DurationFormat {
+    /// The maximum width.
+    pub const MAX_WIDTH : usize = 64;
+    /// The maximum number of decimals, or of significant digits.
+    pub const MAX_PRECISION : usize = 9;
+    /// The maximum length, in bytes, of any result, being that of a
+    /// result padded to [`Self::MAX_WIDTH`] that includes `µ`.
+    pub const MAX_LEN : usize = Self::MAX_WIDTH + 1;
+
+    /// Creates an instance with the default options, which format exactly
+    /// as does `nanoseconds_to_string(nanoseconds, "")`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a format specification, rejecting any characters not
+    /// described by the mini-language.
+    ///
+    /// # Parameters
+    ///
+    /// * `spec` — the specification, comprising, in order, each of the
+    ///   following, all optional:
+    ///   - `+`, causing positive values to include an explicit sign;
+    ///   - `#`, selecting the compound form (see [`Self::compound()`]);
+    ///   - `a`, selecting ASCII-only output (see [`Self::ascii()`]);
+    ///   - the width, as a decimal number, to which the result is
+    ///     right-aligned;
+    ///   - the precision, as either `.` followed by the number of
+    ///     decimals, or `~` followed by the number of significant
+    ///     digits;
+    ///   - the unit, as one of `ns`, `µs` (also accepted as `us`, or with
+    ///     the Greek letter `μ`), `ms`, or `s`;
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::{
+    ///     DurationFormat,
+    ///     ParseDurationFormatError,
+    /// };
+    ///
+    /// assert!(DurationFormat::from_spec("").is_ok());
+    /// assert!(DurationFormat::from_spec("+12~4us").is_ok());
+    ///
+    /// assert_eq!(
+    ///     Err(ParseDurationFormatError::UnexpectedCharacter {
+    ///         position :  2,
+    ///         character : 'x',
+    ///     }),
+    ///     DurationFormat::from_spec("12xs"),
+    /// );
+    /// ```
+    pub fn from_spec(spec : &str) -> Result<Self, ParseDurationFormatError> {
+        let mut df = Self::new();
+        let mut rest = spec;
+
+        if let Some(r) = rest.strip_prefix('+') {
+            df.plus_sign = true;
+            rest = r;
+        }
+
+        if let Some(r) = rest.strip_prefix('#') {
+            df.compound = true;
+            rest = r;
+        }
+
+        if let Some(r) = rest.strip_prefix('a') {
+            df.ascii = true;
+            rest = r;
+        }
+
+        let (width, r) = split_number_(rest);
+
+        if let Some(width) = width {
+            df.width = width.filter(|&w| w <= Self::MAX_WIDTH).ok_or(ParseDurationFormatError::WidthOutOfRange)?;
+            rest = r;
+        }
+
+        let precision_kind : Option<fn(usize) -> Precision_> = match rest.as_bytes().first() {
+            Some(b'.') => Some(Precision_::Decimals),
+            Some(b'~') => Some(Precision_::SignificantDigits),
+            _ => None,
+        };
+
+        if let Some(precision_kind) = precision_kind {
+            let (n, r) = split_number_(&rest[1..]);
+            let n = n.ok_or(ParseDurationFormatError::MissingPrecision)?;
+            let precision = n
+                .map(precision_kind)
+                .filter(Precision_::is_valid_)
+                .ok_or(ParseDurationFormatError::PrecisionOutOfRange)?;
+
+            df.precision = Some(precision);
+            rest = r;
+        }
+
+        if !rest.is_empty() {
+            df.unit = Some(DurationUnit::from_suffix_(rest).ok_or_else(|| {
+                // report the first character at which the remainder ceases
+                // to be the start of a unit, else (for an incomplete unit)
+                // the first character of the remainder
+                let offset = spec.len() - rest.len();
+                let (position, character) = rest
+                    .char_indices()
+                    .find(|&(i, c)| {
+                        let head = &rest[..i + c.len_utf8()];
+
+                        !UNIT_SUFFIXES.iter().any(|&(suffix, _)| suffix.starts_with(head))
+                    })
+                    .unwrap_or_else(|| (0, rest.chars().next().unwrap()));
+
+                ParseDurationFormatError::UnexpectedCharacter {
+                    position : offset + position,
+                    character,
+                }
+            })?);
+        }
+
+        Ok(df)
+    }
+}
+
+// Mutating methods
+
+impl DurationFormat {
+    /// Specifies whether positive values include an explicit sign.
+    pub fn plus_sign(
+        self,
+        plus_sign : bool,
+    ) -> Self {
+        Self {
+            plus_sign,
+            ..self
+        }
+    }
+
+    /// Specifies whether durations of one minute or more are written in
+    /// the compound form of days (`d`), hours (`h`), minutes (`m`), and
+    /// seconds (`s`), in place of the unit and precision options.
+    ///
+    /// The form comprises the largest non-zero component and, unless it
+    /// is zero, the next - as in `"3h"`, `"2m 5.3s"`, and `"1d 4h"` -
+    /// where seconds are written to tenths. As for the other forms, all
+    /// lower-order parts are truncated, never rounded, so a duration is
+    /// never written as longer than it is, and
+    /// [`parse_nanoseconds()`](crate::parse_nanoseconds) of the result
+    /// gives back the duration with those parts removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DurationFormat;
+    ///
+    /// let df = DurationFormat::new().compound(true);
+    ///
+    /// assert_eq!("2m 5.3s", df.format(125_399_000_000));
+    /// assert_eq!("1d 4h", df.format(102_000_000_000_000));
+    /// assert_eq!("5.321s", df.format(5_321_000_000));
+    /// ```
+    pub fn compound(
+        self,
+        compound : bool,
+    ) -> Self {
+        Self {
+            compound,
+            ..self
+        }
+    }
+
+    /// Specifies whether the output is restricted to ASCII, with
+    /// microseconds written as `us` rather than `µs`, for log sinks that
+    /// mangle, or fixed-width columns that miscount, multi-byte UTF-8.
+    ///
+    /// The output is otherwise unchanged (and so is one byte shorter,
+    /// though the same number of characters).
+    pub fn ascii(
+        self,
+        ascii : bool,
+    ) -> Self {
+        Self {
+            ascii,
+            ..self
+        }
+    }
+
+    /// Sets the width, in characters, to which the result is
+    /// right-aligned (with spaces).
+    ///
+    /// # Panics
+    ///
+    /// If `width` is greater than [`Self::MAX_WIDTH`].
+    pub fn width(
+        self,
+        width : usize,
+    ) -> Self {
+        assert!(
+            width <= Self::MAX_WIDTH,
+            "`width` must not be greater than {}, not {width}",
+            Self::MAX_WIDTH
+        );
+
+        Self {
+            width,
+            ..self
+        }
+    }
+
+    /// Sets a fixed unit, in place of the unit chosen by magnitude.
+    ///
+    /// With no precision specified, the value is written exactly, with
+    /// as many decimals as required.
+    pub fn unit(
+        self,
+        unit : DurationUnit,
+    ) -> Self {
+        Self {
+            unit : Some(unit),
+            ..self
+        }
+    }
+
+    /// Sets a fixed number of decimals, replacing any number of
+    /// significant digits.
+    ///
+    /// # Panics
+    ///
+    /// If `decimals` is greater than [`Self::MAX_PRECISION`].
+    pub fn decimals(
+        self,
+        decimals : usize,
+    ) -> Self {
+        self.with_precision_(Precision_::Decimals(decimals))
+    }
+
+    /// Sets a fixed number of significant digits, replacing any number of
+    /// decimals.
+    ///
+    /// Digits of the whole part are never omitted, so the result may have
+    /// more significant digits than requested.
+    ///
+    /// # Panics
+    ///
+    /// If `significant_digits` is 0 or is greater than
+    /// [`Self::MAX_PRECISION`].
+    pub fn significant_digits(
+        self,
+        significant_digits : usize,
+    ) -> Self {
+        self.with_precision_(Precision_::SignificantDigits(significant_digits))
+    }
+}
+
+// Non-mutating methods
+
+impl DurationFormat {
+    /// Formats the given nanosecond count according to the options of the
+    /// instance.
+    ///
+    /// With the default unit and precision, zero is always `"0s"`; in any
+    /// case, zero has no sign.
+    pub fn format(
+        &self,
+        nanoseconds : i64,
+    ) -> NanosecondsStr {
+        let mut buf = [0u8; BUFFER_SIZE];
+        let len = self.write_into_(&mut buf, nanoseconds);
+
+        NanosecondsStr::from_buffer(&buf[..len])
+    }
+
+    /// Writes the result of [`Self::format()`] to `w`, without
+    /// allocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use diagnosticism::DurationFormat;
+    ///
+    /// use std::fmt::Write as _;
+    ///
+    /// let df = DurationFormat::from_spec("8").unwrap();
+    /// let mut line = String::from("elapsed=");
+    ///
+    /// df.write_to(&mut line, 123_456_789).unwrap();
+    /// write!(line, ";").unwrap();
+    ///
+    /// assert_eq!("elapsed= 123.4ms;", line);
+    /// ```
+    pub fn write_to<W : std_fmt::Write + ?Sized>(
+        &self,
+        w : &mut W,
+        nanoseconds : i64,
+    ) -> std_fmt::Result {
+        let mut buf = [0u8; BUFFER_SIZE];
+        let len = self.write_into_(&mut buf, nanoseconds);
+
+        // SAFETY: `buf` holds valid UTF-8 written by this module.
+        w.write_str(unsafe { std_str::from_utf8_unchecked(&buf[..len]) })
+    }
+
+    /// Writes the UTF-8 bytes of the result of [`Self::format()`] to the
+    /// start of `buf`, without allocating, returning their number.
+    ///
+    /// # Panics
+    ///
+    /// If `buf` is shorter than the result, which is never longer than
+    /// [`Self::MAX_LEN`] bytes.
+    pub fn format_into(
+        &self,
+        buf : &mut [u8],
+        nanoseconds : i64,
+    ) -> usize {
+        let mut tmp = [0u8; BUFFER_SIZE];
+        let len = self.write_into_(&mut tmp, nanoseconds);
+
+        assert!(
+            len <= buf.len(),
+            "buffer of {} bytes is too short for the formatted duration of {len} bytes",
+            buf.len()
+        );
+
+        buf[..len].copy_from_slice(&tmp[..len]);
+
+        len
+    }
+}
+
+// Implementation
+
+impl DurationFormat {
+    /// Writes the formatted `nanoseconds` to the start of `buf`, returning
+    /// the number of bytes written.
+    fn write_into_(
+        &self,
+        buf : &mut [u8; BUFFER_SIZE],
+        nanoseconds : i64,
+    ) -> usize {
+        let mut pos = 0;
+
+        let v = nanoseconds.unsigned_abs();
+
+        if nanoseconds < 0 {
+            pos = write_bytes(buf, pos, b"-");
+        } else if self.plus_sign && 0 != nanoseconds {
+            pos = write_bytes(buf, pos, b"+");
+        }
+
+        let suffixes = if self.ascii { &SUFFIXES_ASCII } else { &SUFFIXES };
+
+        pos = match (self.unit, self.precision) {
+            _ if self.compound && v >= COMPOUND_UNITS[2].1 => write_compound(buf, pos, v),
+            (None, None) => write_adaptive(buf, pos, v, suffixes),
+            (unit, precision) => {
+                let unit = unit.unwrap_or_else(|| DurationUnit::for_magnitude_(v));
+
+                write_fixed_(buf, pos, v, unit, precision, suffixes)
+            },
+        };
+
+        // SAFETY: `buf` holds valid UTF-8 written by this module.
+        let num_chars = unsafe { std_str::from_utf8_unchecked(&buf[..pos]) }.chars().count();
+
+        if num_chars < self.width {
+            let num_pad = self.width - num_chars;
+
+            buf.copy_within(..pos, num_pad);
+            buf[..num_pad].fill(b' ');
+
+            pos += num_pad;
+        }
+
+        pos
+    }
+
+    fn with_precision_(
+        self,
+        precision : Precision_,
+    ) -> Self {
+        assert!(
+            precision.is_valid_(),
+            "precision must be in the range [{}, {}], not {precision:?}",
+            precision.min_(),
+            Self::MAX_PRECISION
+        );
+
+        Self {
+            precision : Some(precision),
+            ..self
+        }
+    }
+}
+
+impl DurationUnit {
+    fn for_magnitude_(v : u64) -> Self {
+        match v {
+            0 => Self::Seconds,
+            1..=999 => Self::Nanoseconds,
+            1_000..=999_999 => Self::Microseconds,
+            1_000_000..=999_999_999 => Self::Milliseconds,
+            _ => Self::Seconds,
+        }
+    }
+
+    fn from_suffix_(suffix : &str) -> Option<Self> {
+        UNIT_SUFFIXES.iter().find(|&&(s, _)| s == suffix).map(|&(_, unit)| unit)
+    }
+
+    fn index_(self) -> usize {
+        match self {
+            Self::Nanoseconds => 0,
+            Self::Microseconds => 1,
+            Self::Milliseconds => 2,
+            Self::Seconds => 3,
+        }
+    }
+}
+
+impl Precision_ {
+    fn min_(&self) -> usize {
+        match self {
+            Self::Decimals(_) => 0,
+            Self::SignificantDigits(_) => 1,
+        }
+    }
+
+    fn is_valid_(&self) -> bool {
+        let (Self::Decimals(n) | Self::SignificantDigits(n)) = *self;
+
+        (self.min_()..=DurationFormat::MAX_PRECISION).contains(&n)
+    }
+}
+
+
+// Helper functions
+
+/// Splits any leading decimal digits from `s`, returning their value - or
+/// `None` if it overflows - and the remainder.
+fn split_number_(s : &str) -> (Option<Option<usize>>, &str) {
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+
+    if 0 == len {
+        (None, s)
+    } else {
+        (Some(s[..len].parse().ok()), &s[len..])
+    }
+}
+
+/// Writes the magnitude `v` in the given unit and precision, without
+/// sign, returning the new position.
+fn write_fixed_(
+    buf : &mut [u8],
+    mut pos : usize,
+    v : u64,
+    unit : DurationUnit,
+    precision : Option<Precision_>,
+    suffixes : &[&str; 4],
+) -> usize {
+    let index = unit.index_();
+    let divisor = 1_000u64.pow(index as u32);
+    let num_frac_digits = 3 * index;
+
+    let whole = v / divisor;
+    let frac = v % divisor;
+
+    let decimals = match precision {
+        None => {
+            if 0 == frac {
+                0
+            } else {
+                num_frac_digits - num_trailing_zeros_(frac)
+            }
+        },
+        Some(Precision_::Decimals(n)) => n,
+        Some(Precision_::SignificantDigits(n)) => {
+            if 0 != whole {
+                n.saturating_sub(whole.ilog10() as usize + 1)
+            } else if 0 != frac {
+                // the leading zeros of the fraction are not significant
+                num_frac_digits - (frac.ilog10() as usize + 1) + n
+            } else {
+                n - 1
+            }
+        },
+    };
+
+    pos = write_u64(buf, whole, pos);
+
+    if 0 != decimals {
+        pos = write_bytes(buf, pos, b".");
+
+        let mut place = divisor;
+
+        for _ in 0..decimals {
+            place /= 10;
+
+            let digit = frac.checked_div(place).map_or(0, |d| d % 10);
+
+            pos = write_bytes(buf, pos, &[b'0' + digit as u8]);
+        }
+    }
+
+    write_bytes(buf, pos, suffixes[index].as_bytes())
+}
+
+
+/// Obtains the number of trailing decimal zeros of (the non-zero) `n`.
+fn num_trailing_zeros_(mut n : u64) -> usize {
+    debug_assert!(0 != n);
+
+    let mut num_zeros = 0;
+
+    while 0 == n % 10 {
+        n /= 10;
+        num_zeros += 1;
+    }
+
+    num_zeros
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        DurationFormat,
+        DurationUnit,
+        ParseDurationFormatError,
+    };
+
+    use base_traits::Len;
+
+
+    fn assert_spec(
+        nanoseconds : i64,
+        spec : &str,
+        expected : &str,
+    ) {
+        let df = DurationFormat::from_spec(spec).unwrap();
+
+        assert_eq!(expected, df.format(nanoseconds), "formatting {nanoseconds} with {spec:?}");
+    }
+
+
+    #[test]
+    fn TEST_DurationFormat_DEFAULT() {
+        let df = DurationFormat::new();
+
+        assert_eq!(DurationFormat::from_spec("").unwrap(), df);
+        assert_eq!("123.4ms", df.format(123_456_789));
+        assert_eq!("0s", df.format(0));
+        assert_eq!("-9223372036s", df.format(i64::MIN));
+    }
+
+    #[test]
+    fn TEST_DurationFormat_FIXED_UNIT() {
+        #[rustfmt::skip]
+        let cases = [
+            (            0, "ns", "0ns"),
+            (    1_234_567, "ns", "1234567ns"),
+            (    1_234_567, "us", "1234.567µs"),
+            (    1_234_567, "µs", "1234.567µs"),
+            (    1_234_567, "ms", "1.234567ms"),
+            (    1_234_567, "s",  "0.001234567s"),
+            (    1_500_000, "ms", "1.5ms"),
+            (  -42_000_000, "s",  "-0.042s"),
+            (7_000_000_000, "ms", "7000ms"),
+            (            0, "ms", "0ms"),
+        ];
+
+        for (nanoseconds, spec, expected) in cases {
+            assert_spec(nanoseconds, spec, expected);
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormat_DECIMALS() {
+        #[rustfmt::skip]
+        let cases = [
+            (  123_456_789, ".2ms", "123.45ms"),
+            (  123_456_789, ".0ms", "123ms"),
+            (  123_456_789, ".9s",  "0.123456789s"),
+            (          789, ".2us", "0.78µs"),
+            (          789, ".2ns", "789.00ns"),
+            (    1_050_000, ".3ms", "1.050ms"),
+            (            0, ".2ms", "0.00ms"),
+            (  123_456_789, ".1",   "123.4ms"),
+            (        6_000, ".3",   "6.000µs"),
+            (            0, ".1",   "0.0s"),
+        ];
+
+        for (nanoseconds, spec, expected) in cases {
+            assert_spec(nanoseconds, spec, expected);
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormat_SIGNIFICANT_DIGITS() {
+        #[rustfmt::skip]
+        let cases = [
+            (      123_456_789, "~3",   "123ms"),
+            (      123_456_789, "~5",   "123.45ms"),
+            (            6_000, "~3",   "6.00µs"),
+            (           56_789, "~2",   "56µs"),
+            (           56_789, "~1",   "56µs"),
+            (        1_234_567, "~3s",  "0.00123s"),
+            (                1, "~2s",  "0.0000000010s"),
+            (                0, "~3ms", "0.00ms"),
+            (   12_345_000_000, "~9s",  "12.3450000s"),
+        ];
+
+        for (nanoseconds, spec, expected) in cases {
+            assert_spec(nanoseconds, spec, expected);
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormat_WIDTH_AND_SIGN() {
+        #[rustfmt::skip]
+        let cases = [
+            (  123_456_789, "10",     "   123.4ms"),
+            (        6_789, "8",      " 6.789µs"),
+            (  123_456_789, "3",      "123.4ms"),
+            (  123_456_789, "+10.1ms", "  +123.4ms"),
+            ( -123_456_789, "+10.1ms", "  -123.4ms"),
+            (            0, "+6.1ms",  " 0.0ms"),
+            (       45_000, "+7us",    "  +45µs"),
+        ];
+
+        for (nanoseconds, spec, expected) in cases {
+            assert_spec(nanoseconds, spec, expected);
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormat_BUILDER() {
+        let df = DurationFormat::new()
+            .plus_sign(true)
+            .width(12)
+            .unit(DurationUnit::Seconds)
+            .significant_digits(4);
+
+        assert_eq!(DurationFormat::from_spec("+12~4s").unwrap(), df);
+        assert_eq!("     +1.234s", df.format(1_234_567_890));
+
+        let df = df.decimals(1).plus_sign(false).width(0);
+
+        assert_eq!(DurationFormat::from_spec(".1s").unwrap(), df);
+        assert_eq!("1.2s", df.format(1_234_567_890));
+    }
+
+    #[test]
+    fn TEST_DurationFormat_COMPOUND() {
+        let df = DurationFormat::from_spec("+#10.3ms").unwrap();
+
+        assert_eq!(DurationFormat::new().plus_sign(true).compound(true).width(10).decimals(3).unit(DurationUnit::Milliseconds), df);
+
+        // shorter durations are formatted according to the other options
+        assert_eq!("  +1.234ms", df.format(1_234_567));
+        assert_eq!("+59999.999ms", df.format(59_999_999_999));
+        assert_eq!("   +1m 30s", df.format(90_000_000_000));
+        assert_eq!("   -1d 12h", df.format(-129_600_000_000_000));
+
+        // the longest compound forms are stored inline
+        for v in [i64::MIN, i64::MAX, -3_599_999_999_999, -86_399_999_999_999] {
+            assert!(!DurationFormat::new().compound(true).format(v).is_heap());
+        }
+    }
+
+    #[test]
+    fn TEST_DurationFormat_ASCII() {
+        let df = DurationFormat::new().ascii(true);
+
+        assert_eq!(DurationFormat::from_spec("a").unwrap(), df);
+        assert_eq!(DurationFormat::from_spec("+#a8.1us").unwrap(), df.plus_sign(true).compound(true).width(8).decimals(1).unit(DurationUnit::Microseconds));
+
+        // the length is in bytes, and is the same as the number of
+        // characters only in ASCII mode
+        for v in [1, 1_234, -56_789, 456_789_012, 3_600_000_000_000] {
+            let ascii = df.format(v);
+            let unicode = DurationFormat::new().format(v);
+
+            assert!(ascii.is_ascii());
+            assert_eq!(ascii.as_str(), unicode.replace('µ', "u"));
+            assert_eq!(ascii.as_str().len(), Len::len(&ascii));
+            assert_eq!(ascii.chars().count(), Len::len(&ascii));
+            assert_eq!(unicode.as_str().len(), Len::len(&unicode));
+            assert_eq!(unicode.chars().count(), ascii.chars().count());
+        }
+
+        // the shorter form is stored inline where the other is not
+        let df = DurationFormat::from_spec("a15").unwrap();
+        let s = df.format(1_234);
+
+        assert_eq!("        1.234us", s);
+        assert!(!s.is_heap());
+        assert!(DurationFormat::from_spec("15").unwrap().format(1_234).is_heap());
+    }
+
+    #[test]
+    fn TEST_DurationFormat_MAXIMUM_WIDTH() {
+        let df = DurationFormat::new().width(DurationFormat::MAX_WIDTH).unit(DurationUnit::Microseconds);
+
+        let s = df.format(i64::MIN);
+
+        assert_eq!(DurationFormat::MAX_WIDTH, s.as_str().chars().count());
+        assert!(s.as_str().ends_with(" -9223372036854775.808µs"));
+    }
+
+    #[test]
+    fn TEST_DurationFormat_from_spec_INVALID() {
+        #[rustfmt::skip]
+        let cases = [
+            ("x",     ParseDurationFormatError::UnexpectedCharacter { position : 0, character : 'x' }),
+            ("++",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : '+' }),
+            ("#+",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : '+' }),
+            ("a#",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : '#' }),
+            ("aa",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : 'a' }),
+            ("10#",   ParseDurationFormatError::UnexpectedCharacter { position : 2, character : '#' }),
+            ("10x",   ParseDurationFormatError::UnexpectedCharacter { position : 2, character : 'x' }),
+            (".2.3",  ParseDurationFormatError::UnexpectedCharacter { position : 2, character : '.' }),
+            ("ms+",   ParseDurationFormatError::UnexpectedCharacter { position : 2, character : '+' }),
+            ("mx",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : 'x' }),
+            ("sec",   ParseDurationFormatError::UnexpectedCharacter { position : 1, character : 'e' }),
+            ("m",     ParseDurationFormatError::UnexpectedCharacter { position : 0, character : 'm' }),
+            (" 10",   ParseDurationFormatError::UnexpectedCharacter { position : 0, character : ' ' }),
+            ("-",     ParseDurationFormatError::UnexpectedCharacter { position : 0, character : '-' }),
+            ("10ms ", ParseDurationFormatError::UnexpectedCharacter { position : 4, character : ' ' }),
+            ("ss",    ParseDurationFormatError::UnexpectedCharacter { position : 1, character : 's' }),
+            (".",     ParseDurationFormatError::MissingPrecision),
+            ("~ms",   ParseDurationFormatError::MissingPrecision),
+            ("65",    ParseDurationFormatError::WidthOutOfRange),
+            ("99999999999999999999999", ParseDurationFormatError::WidthOutOfRange),
+            (".10",   ParseDurationFormatError::PrecisionOutOfRange),
+            ("~0",    ParseDurationFormatError::PrecisionOutOfRange),
+        ];
+
+        for (spec, expected) in cases {
+            assert_eq!(Err(expected), DurationFormat::from_spec(spec), "parsing {spec:?}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "`width` must not be greater than 64, not 65")]
+    fn TEST_DurationFormat_width_OUT_OF_RANGE() {
+        let _ = DurationFormat::new().width(65);
+    }
+
+    #[test]
+    #[should_panic(expected = "precision must be in the range [1, 9], not SignificantDigits(0)")]
+    fn TEST_DurationFormat_significant_digits_OUT_OF_RANGE() {
+        let _ = DurationFormat::new().significant_digits(0);
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,273 @@
+// src/diagnostics/time_format/fmt_duration.rs : `FmtDuration`, `FmtElapsed`
+
+use super::format::{
+    write_adaptive,
+    write_bytes,
+    write_u64,
+    SUFFIXES,
+};
+
+use std::{
+    fmt as std_fmt,
+    str as std_str,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+
+/// Large enough for a sign, the 20 digits of the whole seconds of
+/// [`Duration::MAX`], and the suffix.
+const BUFFER_SIZE : usize = 24;
+
+
+/// Adapter that implements [`Display`](std_fmt::Display) and
+/// [`Debug`](std_fmt::Debug) for a [`Duration`], formatting it as does
+/// [`nanoseconds_to_string()`](crate::nanoseconds_to_string), but over the
+/// whole range of [`Duration`], without truncation.
+///
+/// The format flags are honoured as follows:
+///
+/// * sign (`{:+}`) - a non-zero duration is preceded by `+`;
+/// * width (`{:10}`) - the result is padded to the given width with the
+///   given fill and alignment (left, by default);
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::FmtDuration;
+///
+/// use std::time::Duration;
+///
+/// let d = Duration::from_micros(6_789);
+///
+/// assert_eq!("6.789ms", format!("{}", FmtDuration(d)));
+/// assert_eq!("+6.789ms", format!("{:+}", FmtDuration(d)));
+/// assert_eq!("[__6.789ms]", format!("[{:_>9}]", FmtDuration(d)));
+/// assert_eq!("18446744073709551615s", FmtDuration(Duration::MAX).to_string());
+/// ```
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Eq, PartialEq)]
+pub struct FmtDuration(pub Duration);
+
+
+/// Adapter that implements [`Display`](std_fmt::Display) and
+/// [`Debug`](std_fmt::Debug) for the time elapsed since an [`Instant`],
+/// measured when formatted, and written as by [`FmtDuration`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::FmtElapsed;
+///
+/// use std::time::Instant;
+///
+/// let start = Instant::now();
+///
+/// // ... work ...
+///
+/// let s = format!("took {}", FmtElapsed(start));
+///
+/// assert!(s.starts_with("took "));
+/// assert!(s.ends_with('s'));
+/// ```
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(Eq, PartialEq)]
+pub struct FmtElapsed(pub Instant);
+
+
+// Trait implementations
+
+impl std_fmt::Debug for FmtDuration {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        fmt_duration_(self.0, f)
+    }
+}
+
+
+impl std_fmt::Display for FmtDuration {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        fmt_duration_(self.0, f)
+    }
+}
+
+
+impl From<Duration> for FmtDuration {
+    fn from(value : Duration) -> Self {
+        Self(value)
+    }
+}
+
+
+impl std_fmt::Debug for FmtElapsed {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        fmt_duration_(self.0.elapsed(), f)
+    }
+}
+
+
+impl std_fmt::Display for FmtElapsed {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        fmt_duration_(self.0.elapsed(), f)
+    }
+}
+
+
+impl From<Instant> for FmtElapsed {
+    fn from(value : Instant) -> Self {
+        Self(value)
+    }
+}
+
+
+// Helper functions
+
+fn fmt_duration_(
+    d : Duration,
+    f : &mut std_fmt::Formatter<'_>,
+) -> std_fmt::Result {
+    use std_fmt::Write as _;
+
+    let mut buf = [0u8; BUFFER_SIZE];
+    let mut pos = 0;
+
+    if f.sign_plus() && !d.is_zero() {
+        pos = write_bytes(&mut buf, pos, b"+");
+    }
+
+    // from 1000s, the adaptive form is the whole number of seconds, which
+    // is written directly, since the number of nanoseconds may exceed
+    // `u64::MAX`
+    pos = if d.as_secs() >= 1_000 {
+        let pos = write_u64(&mut buf, d.as_secs(), pos);
+
+        write_bytes(&mut buf, pos, b"s")
+    } else {
+        write_adaptive(&mut buf, pos, d.as_nanos() as u64, &SUFFIXES)
+    };
+
+    // SAFETY: `buf` holds valid UTF-8 written by this module.
+    let s = unsafe { std_str::from_utf8_unchecked(&buf[..pos]) };
+
+    let num_padding = f.width().unwrap_or(0).saturating_sub(s.chars().count());
+    let (num_before, num_after) = match f.align() {
+        Some(std_fmt::Alignment::Right) => (num_padding, 0),
+        Some(std_fmt::Alignment::Center) => (num_padding / 2, num_padding - num_padding / 2),
+        Some(std_fmt::Alignment::Left) | None => (0, num_padding),
+    };
+    let fill = f.fill();
+
+    for _ in 0..num_before {
+        f.write_char(fill)?;
+    }
+
+    f.write_str(s)?;
+
+    for _ in 0..num_after {
+        f.write_char(fill)?;
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        FmtDuration,
+        FmtElapsed,
+    };
+
+    use crate::diagnostics::time_format::{
+        format::nanoseconds_to_string,
+        parse::parse_nanoseconds,
+    };
+
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+
+    #[test]
+    fn TEST_FmtDuration_MATCHES_nanoseconds_to_string() {
+        for v in [
+            0,
+            9,
+            789,
+            6_789,
+            1_050,
+            123_456_789,
+            999_974_000,
+            3_063_260_000,
+            789_123_456_789,
+            999_999_999_999,
+            1_000_000_000_000,
+            77_777_777_777_777_777,
+            i64::MAX,
+        ] {
+            let d = Duration::from_nanos(v as u64);
+
+            assert_eq!(nanoseconds_to_string(v, "").as_str(), FmtDuration(d).to_string());
+            assert_eq!(nanoseconds_to_string(v, "+").as_str(), format!("{:+}", FmtDuration(d)));
+            assert_eq!(FmtDuration(d).to_string(), format!("{:?}", FmtDuration(d)));
+        }
+    }
+
+    #[test]
+    fn TEST_FmtDuration_BEYOND_i64() {
+        // as `i64`, the number of nanoseconds would be negative
+        assert_eq!("9223372037s", FmtDuration(Duration::from_nanos(u64::MAX / 2 + 1_000_000_000)).to_string());
+        assert_eq!("18446744073s", FmtDuration(Duration::from_nanos(u64::MAX)).to_string());
+
+        // and beyond `u64`
+        assert_eq!("18446744074s", FmtDuration(Duration::from_secs(18_446_744_074)).to_string());
+        assert_eq!("+18446744073709551615s", format!("{:+}", FmtDuration(Duration::MAX)));
+    }
+
+    #[test]
+    fn TEST_FmtDuration_WIDTH_AND_FILL() {
+        let d = FmtDuration(Duration::from_nanos(6_789));
+
+        assert_eq!("6.789µs", format!("{d:3}"));
+        assert_eq!("6.789µs   ", format!("{d:10}"));
+        assert_eq!("   6.789µs", format!("{d:>10}"));
+        assert_eq!("*6.789µs**", format!("{d:*^10}"));
+        assert_eq!("  +6.789µs", format!("{d:>+10}"));
+        assert_eq!("0s        ", format!("{:+10}", FmtDuration(Duration::ZERO)));
+
+        // precision is not applied
+        assert_eq!("6.789µs", format!("{d:.2}"));
+    }
+
+    #[test]
+    fn TEST_FmtElapsed() {
+        let start = Instant::now() - Duration::from_secs(2);
+
+        let s = FmtElapsed(start).to_string();
+
+        assert!(parse_nanoseconds(&s).unwrap() >= 2_000_000_000, "{s}");
+        assert!(format!("{:+}", FmtElapsed(start)).starts_with("+2"));
+        assert!(format!("{:?}", FmtElapsed::from(start)).starts_with('2'));
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -1,9 +1,17 @@
 // src/diagnostics/time_format/format.rs : `nanoseconds_to_string()`
 
-use super::nanoseconds_str::NanosecondsStr;
+use super::{
+    duration_format::{
+        DurationFormat,
+        ParseDurationFormatError,
+    },
+    nanoseconds_str::NanosecondsStr,
+};
 
+use std::fmt as std_fmt;
 
-const SCALES : [i64; 12] = [
+
+const SCALES : [u64; 12] = [
     1,
     10,
     100,
@@ -18,24 +26,44 @@ const SCALES : [i64; 12] = [
     100_000_000_000,
 ];
 
-const SUFFIXES : [&str; 4] = [
+pub(super) const SUFFIXES : [&str; 4] = [
     "ns",
     "µs",
     "ms",
     "s",
 ];
 
+/// As [`SUFFIXES`], but with `us` in place of the (two-byte) `µs`.
+pub(super) const SUFFIXES_ASCII : [&str; 4] = [
+    "ns",
+    "us",
+    "ms",
+    "s",
+];
+
+/// The components of the compound form, each with its number of
+/// nanoseconds, from largest to smallest.
+pub(super) const COMPOUND_UNITS : [(&str, u64); 4] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+];
+
 
 // API functions
 
 /// Formats a nanosecond count as a compact human-readable duration string,
 /// returning a [`NanosecondsStr`].
 ///
-/// The output adapts the unit (`ns`, `µs`, `ms`, `s`) and decimal precision
-/// to keep roughly three significant digits in the numeric portion.
+/// The output adapts the unit (`ns`, `µs`, `ms`, `s`) and decimal
+/// precision to keep roughly three significant digits in the numeric
+/// portion.
 ///
 /// Behaviour matches [`Diagnosticism.Python`][dp] 0.16.0
-/// `nanoseconds_to_string()`.
+/// `nanoseconds_to_string()`, except that the leading zeros of the
+/// fraction are written for a single-digit whole part (e.g. `3.063s`,
+/// where Diagnosticism.Python gives `3.63s`).
 ///
 /// # Parameters
 ///
@@ -49,33 +77,173 @@ const SUFFIXES : [&str; 4] = [
 /// A [`NanosecondsStr`] holding the formatted duration. Zero is always
 /// `"0s"` with no sign.
 ///
+/// # Note:
+/// For a fixed unit, a fixed precision, alignment, the compound form, or
+/// ASCII-only output, use [`try_nanoseconds_to_string()`] or
+/// [`DurationFormat`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::nanoseconds_to_string;
+///
+/// assert_eq!("123.4ms", nanoseconds_to_string(123_456_789, ""));
+/// assert_eq!("+123.4ms", nanoseconds_to_string(123_456_789, "+"));
+/// assert_eq!("10800s", nanoseconds_to_string(10_800_000_000_000, ""));
+///
+/// // characters other than `+` are ignored
+/// assert_eq!("+123.4ms", nanoseconds_to_string(123_456_789, "x+"));
+/// assert_eq!("123.4ms", nanoseconds_to_string(123_456_789, "7.2s"));
+/// ```
+///
 /// [dp]: https://github.com/synesissoftware/Diagnosticism.Python
 pub fn nanoseconds_to_string(
     nanoseconds : i64,
     format_spec : &str,
 ) -> NanosecondsStr {
-    let mut v = nanoseconds;
+    legacy_duration_format_(format_spec).format(nanoseconds)
+}
+
+/// Formats a nanosecond count as a duration string, returning a
+/// [`NanosecondsStr`], according to a `format_spec` in the mini-language
+/// described by [`DurationFormat::from_spec()`].
+///
+/// With an empty `format_spec`, or `"+"`, the result is as obtained from
+/// [`nanoseconds_to_string()`]; further, e.g., `"#"` selects the compound
+/// form (e.g. `"2m 5.3s"`) for durations of a minute or more, `"a"`
+/// writes microseconds as `us` rather than `µs`, and `"8.2ms"` formats in
+/// milliseconds, with two decimals, right-aligned to 8 characters.
+///
+/// # Errors
+///
+/// [`ParseDurationFormatError`] if [`DurationFormat::from_spec()`] fails
+/// for `format_spec`.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::try_nanoseconds_to_string;
+///
+/// let f = |nanoseconds, format_spec| try_nanoseconds_to_string(nanoseconds, format_spec).unwrap();
+///
+/// assert_eq!("+123.4ms", f(123_456_789, "+").as_str());
+/// assert_eq!("  0.12s", f(123_456_789, "7.2s").as_str());
+/// assert_eq!("123.45ms", f(123_456_789, "~5").as_str());
+/// assert_eq!("3h", f(10_800_000_000_000, "#").as_str());
+/// assert_eq!("6.789us", f(6_789, "a").as_str());
+///
+/// assert!(try_nanoseconds_to_string(123_456_789, "+x").is_err());
+/// ```
+pub fn try_nanoseconds_to_string(
+    nanoseconds : i64,
+    format_spec : &str,
+) -> Result<NanosecondsStr, ParseDurationFormatError> {
+    DurationFormat::from_spec(format_spec).map(|df| df.format(nanoseconds))
+}
 
-    let sign_byte = if v < 0 {
-        v = -v;
+/// Writes a nanosecond count as a duration string to `w`, as formatted
+/// by [`nanoseconds_to_string()`], but without allocating.
+///
+/// `format_spec` is interpreted as described for
+/// [`nanoseconds_to_string()`]; for the mini-language of
+/// [`DurationFormat::from_spec()`], use [`DurationFormat::write_to()`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::write_nanoseconds;
+///
+/// let mut line = String::from("elapsed=");
+///
+/// write_nanoseconds(&mut line, 6_789, "").unwrap();
+///
+/// assert_eq!("elapsed=6.789µs", line);
+/// ```
+pub fn write_nanoseconds<W : std_fmt::Write + ?Sized>(
+    w : &mut W,
+    nanoseconds : i64,
+    format_spec : &str,
+) -> std_fmt::Result {
+    legacy_duration_format_(format_spec).write_to(w, nanoseconds)
+}
 
-        Some(b'-')
-    } else if format_spec.contains('+') {
-        Some(b'+')
+/// Writes the UTF-8 bytes of a nanosecond count as a duration string, as
+/// formatted by [`nanoseconds_to_string()`], to the start of `buf`,
+/// without allocating, returning their number.
+///
+/// `format_spec` is interpreted as described for
+/// [`nanoseconds_to_string()`]; for the mini-language of
+/// [`DurationFormat::from_spec()`], use [`DurationFormat::format_into()`].
+///
+/// # Panics
+///
+/// If `buf` is shorter than the result, which is never longer than
+/// [`DurationFormat::MAX_LEN`] bytes.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     format_nanoseconds_into,
+///     DurationFormat,
+/// };
+///
+/// let mut buf = [0u8; DurationFormat::MAX_LEN];
+///
+/// let len = format_nanoseconds_into(&mut buf, -123_456_789, "");
+///
+/// assert_eq!(b"-123.4ms", &buf[..len]);
+/// ```
+pub fn format_nanoseconds_into(
+    buf : &mut [u8],
+    nanoseconds : i64,
+    format_spec : &str,
+) -> usize {
+    legacy_duration_format_(format_spec).format_into(buf, nanoseconds)
+}
+
+
+// Helper functions
+
+/// Obtains the format for a `format_spec` of [`nanoseconds_to_string()`],
+/// in which only `+` is recognised.
+fn legacy_duration_format_(format_spec : &str) -> DurationFormat {
+    DurationFormat::new().plus_sign(format_spec.contains('+'))
+}
+
+
+fn scale_index(n : u64) -> (usize, u64) {
+    debug_assert!(n > 0);
+
+    let oom = if n >= 100_000_000_000 {
+        11
     } else {
-        None
+        n.ilog10() as usize
     };
 
+    (oom, SCALES[oom])
+}
+
+
+/// Writes the magnitude `v` in the default (adaptive) unit and precision,
+/// without sign, with a unit suffix from `suffixes`, returning the new
+/// position.
+pub(super) fn write_adaptive(
+    buf : &mut [u8],
+    pos : usize,
+    v : u64,
+    suffixes : &[&str; 4],
+) -> usize {
     if v == 0 {
-        return NanosecondsStr::from_buffer(b"0s");
+        return write_bytes(buf, pos, b"0s");
     }
 
     let (oom, divisor) = scale_index(v);
 
-    let suffix = SUFFIXES[oom / 3];
+    let suffix = suffixes[oom / 3];
 
     if oom < 3 {
-        return format_parts(sign_byte, v, 0, suffix);
+        return write_parts(buf, pos, v, 0, suffix);
     }
 
     let divisor_0 = divisor / 1_000;
@@ -90,68 +258,89 @@ pub fn nanoseconds_to_string(
         10
     };
 
-    v /= divisor_0;
+    let v = v / divisor_0;
 
     let whole = v / divisor_1;
     let frac = v - (whole * divisor_1);
 
-    format_parts(sign_byte, whole, frac, suffix)
+    write_parts(buf, pos, whole, frac, suffix)
 }
 
 
-// Helper functions
+/// Writes the magnitude `v`, which must be at least one minute, in the
+/// compound form, without sign, returning the new position.
+///
+/// The form comprises the largest non-zero component and, unless it is
+/// zero, the next component, which - for seconds - may include tenths.
+/// All lower-order parts are truncated.
+pub(super) fn write_compound(
+    buf : &mut [u8],
+    mut pos : usize,
+    v : u64,
+) -> usize {
+    debug_assert!(v >= COMPOUND_UNITS[2].1);
 
-fn scale_index(n : i64) -> (usize, i64) {
-    debug_assert!(n > 0);
+    let i = COMPOUND_UNITS.iter().position(|&(_, scale)| v >= scale).unwrap();
 
-    let oom = if n >= 100_000_000_000 {
-        11
+    let (major_suffix, major_scale) = COMPOUND_UNITS[i];
+    let (minor_suffix, minor_scale) = COMPOUND_UNITS[i + 1];
+
+    pos = write_u64(buf, v / major_scale, pos);
+    pos = write_bytes(buf, pos, major_suffix.as_bytes());
+
+    let remainder = v % major_scale;
+
+    // seconds are written to tenths, other components to units
+    let minor_tenths = if "s" == minor_suffix {
+        remainder / (minor_scale / 10)
     } else {
-        n.ilog10() as usize
+        10 * (remainder / minor_scale)
     };
 
-    (oom, SCALES[oom])
-}
+    if 0 != minor_tenths {
+        pos = write_bytes(buf, pos, b" ");
+        pos = write_u64(buf, minor_tenths / 10, pos);
 
+        if 0 != minor_tenths % 10 {
+            pos = write_bytes(buf, pos, &[b'.', b'0' + (minor_tenths % 10) as u8]);
+        }
 
-fn format_parts(
-    sign_byte : Option<u8>,
-    whole : i64,
-    frac : i64,
-    suffix : &str,
-) -> NanosecondsStr {
-    let mut buf = [0u8; 24];
-    let mut pos = 0usize;
+        pos = write_bytes(buf, pos, minor_suffix.as_bytes());
+    }
 
-    if let Some(b) = sign_byte {
-        buf[pos] = b;
+    pos
+}
 
-        pos += 1;
-    }
 
-    pos = write_u64(&mut buf, whole as u64, pos);
+fn write_parts(
+    buf : &mut [u8],
+    mut pos : usize,
+    whole : u64,
+    frac : u64,
+    suffix : &str,
+) -> usize {
+    pos = write_u64(buf, whole, pos);
 
     if frac != 0 && whole <= 999 {
         buf[pos] = b'.';
 
         pos += 1;
 
+        // the fraction has as many digits as the whole part lacks of 4
         if whole > 99 {
-            pos = write_u64(&mut buf, frac as u64, pos);
+            pos = write_u64(buf, frac, pos);
         } else if whole > 9 {
-            pos = write_frac_min_width_2(&mut buf, pos, frac);
+            pos = write_frac_min_width(buf, pos, frac, 2);
         } else {
-            pos = write_u64(&mut buf, frac as u64, pos);
+            pos = write_frac_min_width(buf, pos, frac, 3);
         }
     }
 
-    pos = write_bytes(&mut buf, pos, suffix.as_bytes());
-
-    NanosecondsStr::from_buffer(&buf[..pos])
+    write_bytes(buf, pos, suffix.as_bytes())
 }
 
 
-fn write_u64(
+pub(super) fn write_u64(
     buf : &mut [u8],
     mut n : u64,
     mut pos : usize,
@@ -178,25 +367,27 @@ fn write_u64(
 }
 
 
-fn write_frac_min_width_2(
+fn write_frac_min_width(
     buf : &mut [u8],
     pos : usize,
-    frac : i64,
+    frac : u64,
+    width : u32,
 ) -> usize {
-    debug_assert!((0..100).contains(&frac));
+    debug_assert!(frac < 10u64.pow(width));
 
-    if frac >= 10 {
-        write_u64(buf, frac as u64, pos)
-    } else {
-        buf[pos] = b'0';
-        buf[pos + 1] = (frac as u8) + b'0';
+    let mut end = pos;
+
+    for place in (0..width).rev() {
+        buf[end] = ((frac / 10u64.pow(place)) % 10) as u8 + b'0';
 
-        pos + 2
+        end += 1;
     }
+
+    end
 }
 
 
-fn write_bytes(
+pub(super) fn write_bytes(
     buf : &mut [u8],
     pos : usize,
     bytes : &[u8],
@@ -211,7 +402,14 @@ fn write_bytes(
 mod tests {
     #![allow(non_snake_case)]
 
-    use super::nanoseconds_to_string;
+    use super::{
+        format_nanoseconds_into,
+        nanoseconds_to_string,
+        try_nanoseconds_to_string,
+        write_nanoseconds,
+    };
+
+    use crate::diagnostics::time_format::DurationFormat;
 
 
     fn assert_ns(
@@ -221,8 +419,15 @@ mod tests {
     ) {
         assert_eq!(
             expected,
-            nanoseconds_to_string(nanoseconds, format_spec),
+            try_nanoseconds_to_string(nanoseconds, format_spec).unwrap(),
         );
+
+        if format_spec.is_empty() || "+" == format_spec {
+            assert_eq!(
+                expected,
+                nanoseconds_to_string(nanoseconds, format_spec),
+            );
+        }
     }
 
 
@@ -370,6 +575,162 @@ mod tests {
         assert_ns(-999_800_000, "+", "-999.8ms");
         assert_ns(-999_974_000, "+", "-999.9ms");
     }
+
+
+    #[rustfmt::skip]
+    #[test]
+    fn TEST_FRACTION_LEADING_ZEROS() {
+        assert_ns(     1_050,    "",  "1.050µs");
+        assert_ns(     1_005,    "",  "1.005µs");
+        assert_ns(    10_050,    "",  "10.05µs");
+        assert_ns( 3_063_260_000, "",  "3.063s");
+        assert_ns(-3_063_260_000, "", "-3.063s");
+    }
+
+
+    #[rustfmt::skip]
+    #[test]
+    fn TEST_ASCII() {
+        assert_ns(       6_789, "a",     "6.789us");
+        assert_ns(     -56_789, "+a",   "-56.78us");
+        assert_ns(      56_789, "+a",   "+56.78us");
+        assert_ns(         789, "a",     "789ns");
+        assert_ns( 123_456_789, "a",     "123.4ms");
+        assert_ns(     123_456, "a.1us", "123.4us");
+        assert_ns(     123_456, "a10",   "   123.4us");
+        assert_ns(     123_456, "10",    "   123.4µs");
+    }
+
+
+    #[rustfmt::skip]
+    #[test]
+    fn TEST_FORMAT_SPEC() {
+        assert_ns(123_456_789, "10",     "   123.4ms");
+        assert_ns(123_456_789, ".2s",    "0.12s");
+        assert_ns(123_456_789, "+~4us", "+123456µs");
+        assert_ns(        -12, "6ns",    " -12ns");
+    }
+
+
+    #[test]
+    fn TEST_COMPOUND_STRINGS() {
+        #[rustfmt::skip]
+        let cases = [
+            (                     0, "0s"),
+            (           123_456_789, "123.4ms"),
+            (        59_999_999_999, "59.99s"),
+            (        60_000_000_000, "1m"),
+            (        60_099_999_999, "1m"),
+            (        60_100_000_000, "1m 0.1s"),
+            (       125_300_000_000, "2m 5.3s"),
+            (       125_000_000_000, "2m 5s"),
+            (     3_599_999_999_999, "59m 59.9s"),
+            (     3_600_000_000_000, "1h"),
+            (     3_659_999_999_999, "1h"),
+            (     3_660_000_000_000, "1h 1m"),
+            (    10_800_000_000_000, "3h"),
+            (    86_399_999_999_999, "23h 59m"),
+            (   100_800_000_000_000, "1d 4h"),
+            (   172_800_000_000_000, "2d"),
+            ( 9_223_372_036_854_775_807, "106751d 23h"),
+        ];
+
+        for (nanoseconds, expected) in cases {
+            assert_ns(nanoseconds, "#", expected);
+        }
+
+        assert_ns(-125_300_000_000, "#", "-2m 5.3s");
+        assert_ns(125_300_000_000, "+#", "+2m 5.3s");
+        assert_ns(i64::MIN, "#", "-106751d 23h");
+    }
+
+
+    #[test]
+    fn TEST_write_nanoseconds_AND_format_nanoseconds_into_MATCH_nanoseconds_to_string() {
+        let mut buf = [0u8; DurationFormat::MAX_LEN];
+
+        for (nanoseconds, format_spec) in [
+            (0, ""),
+            (6_789, ""),
+            (-123_456_789, "+"),
+            (56_789, "a"),
+            (10_800_000_000_000, "#"),
+            (i64::MIN, ""),
+            (i64::MIN, "a64.9us"),
+        ] {
+            let expected = nanoseconds_to_string(nanoseconds, format_spec);
+
+            let mut s = String::from("[");
+
+            write_nanoseconds(&mut s, nanoseconds, format_spec).unwrap();
+
+            assert_eq!(format!("[{expected}"), s);
+
+            let len = format_nanoseconds_into(&mut buf, nanoseconds, format_spec);
+
+            assert_eq!(expected.as_bytes(), &buf[..len]);
+        }
+
+        // the maximum length is reached only by a full-width result with
+        // `µ`, which requires the mini-language
+        let df = DurationFormat::from_spec("64.9us").unwrap();
+
+        assert_eq!(DurationFormat::MAX_LEN, df.format_into(&mut buf, i64::MIN));
+    }
+
+    #[test]
+    fn TEST_format_nanoseconds_into_EXACT_BUFFER() {
+        let mut buf = [b'x'; 9];
+
+        assert_eq!(8, format_nanoseconds_into(&mut buf[..8], 6_789, ""));
+        assert_eq!(b"6.789\xC2\xB5sx", &buf);
+    }
+
+    #[test]
+    #[should_panic(expected = "buffer of 6 bytes is too short for the formatted duration of 8 bytes")]
+    fn TEST_format_nanoseconds_into_SHORT_BUFFER() {
+        let _ = format_nanoseconds_into(&mut [0u8; 6], 6_789, "");
+    }
+
+    #[test]
+    fn TEST_FORMAT_SPEC_LEGACY() {
+        let mut buf = [0u8; DurationFormat::MAX_LEN];
+
+        // only `+` is recognised, wherever it is, and all else is ignored
+        for (expected, nanoseconds, format_spec) in [
+            ("+1ns", 1, "+x"),
+            ("+6.789µs", 6_789, "x+"),
+            ("6.789µs", 6_789, "8.x"),
+            ("-6.789µs", -6_789, "99"),
+            ("6.789µs", 6_789, "a10.2ms"),
+            ("10800s", 10_800_000_000_000, "#"),
+            ("0s", 0, "+~"),
+        ] {
+            assert_eq!(expected, nanoseconds_to_string(nanoseconds, format_spec).as_str());
+
+            let mut s = String::new();
+
+            write_nanoseconds(&mut s, nanoseconds, format_spec).unwrap();
+
+            assert_eq!(expected, s);
+
+            let len = format_nanoseconds_into(&mut buf, nanoseconds, format_spec);
+
+            assert_eq!(expected.as_bytes(), &buf[..len]);
+        }
+    }
+
+    #[test]
+    fn TEST_try_nanoseconds_to_string() {
+        assert_eq!("+6.789µs", try_nanoseconds_to_string(6_789, "+").unwrap().as_str());
+        assert_eq!("  6.78µs", try_nanoseconds_to_string(6_789, "8.2").unwrap().as_str());
+
+        assert_eq!(
+            "unexpected character 'x' at position 1",
+            try_nanoseconds_to_string(1, "+x").unwrap_err().to_string(),
+        );
+        assert!(try_nanoseconds_to_string(1, "99").is_err());
+    }
 }
 
 
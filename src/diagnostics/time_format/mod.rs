@@ -1,12 +1,15 @@
-// src/diagnostics/time_format/mod.rs : duration formatting
+// src/diagnostics/time_format/mod.rs : duration formatting and parsing
 
 // NOTE: this work was brought in from **asynkio** via **Diagnosticism.Python**
 // 0.16.0
 
 use crate::macros::declare_and_publish;
 
-declare_and_publish!(format, nanoseconds_to_string);
+declare_and_publish!(duration_format, DurationFormat, DurationUnit, ParseDurationFormatError);
+declare_and_publish!(fmt_duration, FmtDuration, FmtElapsed);
+declare_and_publish!(format, format_nanoseconds_into, nanoseconds_to_string, try_nanoseconds_to_string, write_nanoseconds);
 declare_and_publish!(nanoseconds_str, NanosecondsStr);
+declare_and_publish!(parse, parse_nanoseconds, ParseNanosecondsError);
 
 
 // ///////////////////////////// end of file //////////////////////////// //
@@ -89,7 +89,7 @@ impl NanosecondsStr {
     }
 
     #[cfg(test)]
-    fn is_heap(&self) -> bool {
+    pub(in crate::diagnostics::time_format) fn is_heap(&self) -> bool {
         matches!(self.inner, NanosecondsStrInner::Heap(_))
     }
 }
@@ -222,6 +222,58 @@ impl PartialEq<NanosecondsStr> for str {
 }
 
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for NanosecondsStr {
+    fn serialize<S>(
+        &self,
+        serializer : S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S : serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NanosecondsStr {
+    fn deserialize<D>(deserializer : D) -> Result<Self, D::Error>
+    where
+        D : serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = NanosecondsStr;
+
+            fn expecting(
+                &self,
+                f : &mut std_fmt::Formatter<'_>,
+            ) -> std_fmt::Result {
+                f.write_str("a duration string, such as \"123.4ms\"")
+            }
+
+            fn visit_str<E>(
+                self,
+                v : &str,
+            ) -> Result<Self::Value, E>
+            where
+                E : serde::de::Error,
+            {
+                // only strings that denote a duration are accepted
+                match super::parse::parse_nanoseconds(v) {
+                    Ok(_) => Ok(NanosecondsStr::from_buffer(v.as_bytes())),
+                    Err(e) => Err(E::custom(e)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
     #![allow(non_snake_case)]
@@ -480,6 +532,39 @@ mod tests {
             assert_eq!(expected.len(), Len::len(&s));
         }
     }
+
+    #[cfg(feature = "serde")]
+    mod TEST_serde {
+        #![allow(non_snake_case)]
+
+        use super::*;
+
+
+        #[test]
+        fn TEST_SERIALIZES_AS_STRING() {
+            let s = nanoseconds_to_string(123_456_789, "");
+
+            assert_eq!("\"123.4ms\"", serde_json::to_string(&s).unwrap());
+        }
+
+        #[test]
+        fn TEST_ROUND_TRIP() {
+            for v in [0, 9, 6_789, 123_456_789, -9_123_456_789] {
+                let s = nanoseconds_to_string(v, "");
+                let json = serde_json::to_string(&s).unwrap();
+                let s2 : NanosecondsStr = serde_json::from_str(&json).unwrap();
+
+                assert_eq!(s, s2);
+            }
+        }
+
+        #[test]
+        fn TEST_REJECTS_NON_DURATION() {
+            assert!(serde_json::from_str::<NanosecondsStr>("\"abc\"").is_err());
+            assert!(serde_json::from_str::<NanosecondsStr>("\"12\"").is_err());
+            assert!(serde_json::from_str::<NanosecondsStr>("12").is_err());
+        }
+    }
 }
 
 
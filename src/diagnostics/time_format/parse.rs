@@ -0,0 +1,377 @@
+// src/diagnostics/time_format/parse.rs : `parse_nanoseconds()`
+
+use std::{
+    error as std_error,
+    fmt as std_fmt,
+};
+
+
+/// The recognised unit suffixes, each with its number of nanoseconds.
+///
+/// NOTE: the order is significant, since `"s"` is a suffix of each of the
+/// others that end with it.
+const UNITS : [(&str, u64); 9] = [
+    ("ns", 1),
+    ("µs", 1_000),
+    ("μs", 1_000),
+    ("us", 1_000),
+    ("ms", 1_000_000),
+    ("s", 1_000_000_000),
+    ("m", 60_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("d", 86_400_000_000_000),
+];
+
+
+/// Error type returned by [`parse_nanoseconds()`].
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub enum ParseNanosecondsError {
+    /// The string is empty (or contains only a sign).
+    Empty,
+    /// The string does not end with a recognised unit suffix.
+    InvalidUnit,
+    /// The numeric portion of the string is not a valid decimal number,
+    /// or the components of a compound string are not whole numbers of
+    /// days, hours, or minutes (but for the last) in descending order of
+    /// unit.
+    InvalidNumber,
+    /// The value cannot be represented as an `i64` number of nanoseconds.
+    OutOfRange,
+}
+
+// Trait implementations
+
+impl std_fmt::Display for ParseNanosecondsError {
+    fn fmt(
+        &self,
+        f : &mut std_fmt::Formatter<'_>,
+    ) -> std_fmt::Result {
+        let s = match self {
+            Self::Empty => "empty duration string",
+            Self::InvalidUnit => "missing or unrecognised duration unit",
+            Self::InvalidNumber => "invalid number in duration string",
+            Self::OutOfRange => "duration out of range",
+        };
+
+        f.write_str(s)
+    }
+}
+
+
+impl std_error::Error for ParseNanosecondsError {}
+
+
+// API functions
+
+/// Parses a duration string into a number of nanoseconds.
+///
+/// Every form produced by [`crate::nanoseconds_to_string`] and
+/// [`crate::try_nanoseconds_to_string`] is accepted, including the
+/// compound form (e.g. `"2m 5.3s"`) and results padded to a width, such
+/// that configuration may use the same notation as logs (e.g.
+/// `timeout = "250ms"`).
+///
+/// # Parameters
+///
+/// * `s` — the duration string, comprising, after any leading and
+///   trailing spaces, an optional sign (`-` or `+`)
+///   followed by one or more components separated by single spaces, each
+///   comprising a decimal number and a unit suffix, which may be one of
+///   `ns`, `µs` (also accepted as `us`, or with the Greek letter `μ`),
+///   `ms`, `s`, `m`, `h`, or `d`; the components must be in descending
+///   order of unit, and all but the last must be whole numbers of days,
+///   hours, or minutes; the last may have a fractional part;
+///
+/// # Returns
+///
+/// The number of nanoseconds, with any fractional digits beyond
+/// nanosecond precision truncated.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::parse_nanoseconds;
+///
+/// assert_eq!(Ok(123_400_000), parse_nanoseconds("123.4ms"));
+/// assert_eq!(Ok(1_230_000_000), parse_nanoseconds("1.23s"));
+/// assert_eq!(Ok(-7), parse_nanoseconds("-7ns"));
+/// assert_eq!(Ok(45_000), parse_nanoseconds("+45µs"));
+/// assert_eq!(Ok(45_000), parse_nanoseconds("45us"));
+/// assert_eq!(Ok(125_300_000_000), parse_nanoseconds("2m 5.3s"));
+/// assert_eq!(Ok(100_800_000_000_000), parse_nanoseconds("1d 4h"));
+/// assert_eq!(Ok(-250_000_000), parse_nanoseconds("   -250ms"));
+/// ```
+pub fn parse_nanoseconds(s : &str) -> Result<i64, ParseNanosecondsError> {
+    let s = s.trim_matches(' ');
+
+    let (negative, s) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+
+    if s.is_empty() {
+        return Err(ParseNanosecondsError::Empty);
+    }
+
+    let mut components = s.split(' ');
+    let last = components.next_back().unwrap_or(s);
+
+    // the magnitude is accumulated unsigned, so that that of `i64::MIN`
+    // may be represented
+    let mut v = 0u64;
+    let mut prev_scale = u64::MAX;
+
+    for component in components {
+        let (n, scale) = parse_whole_compound_component_(component, prev_scale)?;
+
+        v = n
+            .checked_mul(scale)
+            .and_then(|n| v.checked_add(n))
+            .ok_or(ParseNanosecondsError::OutOfRange)?;
+
+        prev_scale = scale;
+    }
+
+    let (last, scale) = parse_component_(last)?;
+
+    if scale >= prev_scale {
+        return Err(ParseNanosecondsError::InvalidNumber);
+    }
+
+    v = v.checked_add(last).ok_or(ParseNanosecondsError::OutOfRange)?;
+
+    if negative {
+        0i64.checked_sub_unsigned(v)
+    } else {
+        i64::try_from(v).ok()
+    }
+    .ok_or(ParseNanosecondsError::OutOfRange)
+}
+
+
+// Helper functions
+
+/// Parses a non-final component of a compound string, which must be a
+/// whole number of days, hours, or minutes, of a unit smaller than
+/// `prev_scale`, returning the number and the unit's scale.
+fn parse_whole_compound_component_(
+    component : &str,
+    prev_scale : u64,
+) -> Result<(u64, u64), ParseNanosecondsError> {
+    UNITS[6..]
+        .iter()
+        .filter(|&&(_, scale)| scale < prev_scale)
+        .find_map(|&(suffix, scale)| component.strip_suffix(suffix).map(|number| (number, scale)))
+        .filter(|(number, _)| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or(ParseNanosecondsError::InvalidNumber)
+        .and_then(|(number, scale)| {
+            number
+                .parse::<u64>()
+                .map(|n| (n, scale))
+                .map_err(|_| ParseNanosecondsError::OutOfRange)
+        })
+}
+
+/// Parses a single component, comprising a decimal number with optional
+/// fractional part and a unit suffix, returning its number of
+/// nanoseconds and the unit's scale.
+fn parse_component_(s : &str) -> Result<(u64, u64), ParseNanosecondsError> {
+    let (number, scale) = UNITS
+        .iter()
+        .find_map(|&(suffix, scale)| s.strip_suffix(suffix).map(|number| (number, scale)))
+        .ok_or(ParseNanosecondsError::InvalidUnit)?;
+
+    let (whole, frac) = match number.split_once('.') {
+        Some((whole, frac)) if !frac.is_empty() => (whole, frac),
+        Some(_) => return Err(ParseNanosecondsError::InvalidNumber),
+        None => (number, ""),
+    };
+
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseNanosecondsError::InvalidNumber);
+    }
+
+    if !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseNanosecondsError::InvalidNumber);
+    }
+
+    let mut v = whole
+        .parse::<u64>()
+        .ok()
+        .and_then(|whole| whole.checked_mul(scale))
+        .ok_or(ParseNanosecondsError::OutOfRange)?;
+
+    let mut place = scale;
+
+    for digit in frac.bytes() {
+        place /= 10;
+
+        if 0 == place {
+            break;
+        }
+
+        v = v
+            .checked_add(u64::from(digit - b'0') * place)
+            .ok_or(ParseNanosecondsError::OutOfRange)?;
+    }
+
+    Ok((v, scale))
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        parse_nanoseconds,
+        ParseNanosecondsError,
+    };
+
+    use super::super::format::{
+        nanoseconds_to_string,
+        try_nanoseconds_to_string,
+    };
+
+
+    #[test]
+    fn TEST_parse_nanoseconds_FORMS() {
+        #[rustfmt::skip]
+        let cases = [
+            (  "0s",                        0),
+            (  "9ns",                       9),
+            (  "6.789µs",               6_789),
+            (  "6.789μs",               6_789),
+            (  "6.789us",               6_789),
+            (  "56.78µs",              56_780),
+            (  "123.4ms",         123_400_000),
+            (  "1.23s",         1_230_000_000),
+            (  "9.123s",        9_123_000_000),
+            (  "-7ns",                     -7),
+            (  "+45µs",                45_000),
+            (  "250ms",           250_000_000),
+            (  "1.234567891s",  1_234_567_891),
+            (  "1.2345678919s", 1_234_567_891),
+            (  "3h",           10_800_000_000_000),
+            (  "1.5h",          5_400_000_000_000),
+            (  "2m 5.3s",         125_300_000_000),
+            (  "1d 4h",       100_800_000_000_000),
+            (  "1d 30s",       86_430_000_000_000),
+            (  "-1h 1m",       -3_660_000_000_000),
+            (  "1h 1m 1s",      3_661_000_000_000),
+            (  "1m 500ms",         60_500_000_000),
+            (  "   250ms",        250_000_000),
+            (  "250ms   ",        250_000_000),
+            (  "  -1m 5s ",       -65_000_000_000),
+            (  "-9223372036854775808ns",  i64::MIN),
+            (  "9223372036854775807ns",   i64::MAX),
+        ];
+
+        for (s, expected) in cases {
+            assert_eq!(Ok(expected), parse_nanoseconds(s), "parsing {s:?}");
+        }
+    }
+
+    #[test]
+    fn TEST_parse_nanoseconds_INVALID() {
+        #[rustfmt::skip]
+        let cases = [
+            ("",             ParseNanosecondsError::Empty),
+            ("-",            ParseNanosecondsError::Empty),
+            ("s",            ParseNanosecondsError::InvalidNumber),
+            ("ms",           ParseNanosecondsError::InvalidNumber),
+            ("1",            ParseNanosecondsError::InvalidUnit),
+            ("1w",           ParseNanosecondsError::InvalidUnit),
+            ("1m 2",         ParseNanosecondsError::InvalidUnit),
+            ("   ",          ParseNanosecondsError::Empty),
+            ("- 1ns",        ParseNanosecondsError::InvalidNumber),
+            ("1m 2h",        ParseNanosecondsError::InvalidNumber),
+            ("1m 2m",        ParseNanosecondsError::InvalidNumber),
+            ("1h  2m",       ParseNanosecondsError::InvalidNumber),
+            ("1.5h 2m",      ParseNanosecondsError::InvalidNumber),
+            ("1s 2ms",       ParseNanosecondsError::InvalidNumber),
+            ("1m -2s",       ParseNanosecondsError::InvalidNumber),
+            ("106752d",      ParseNanosecondsError::OutOfRange),
+            ("106751d 24h",  ParseNanosecondsError::OutOfRange),
+            ("1.ms",         ParseNanosecondsError::InvalidNumber),
+            (".5ms",         ParseNanosecondsError::InvalidNumber),
+            ("1.2.3ms",      ParseNanosecondsError::InvalidNumber),
+            ("1 ms",         ParseNanosecondsError::InvalidNumber),
+            ("--1ns",        ParseNanosecondsError::InvalidNumber),
+            ("x1ns",         ParseNanosecondsError::InvalidNumber),
+            ("99999999999s", ParseNanosecondsError::OutOfRange),
+            ("9223372036854775808ns",  ParseNanosecondsError::OutOfRange),
+            ("-9223372036854775809ns", ParseNanosecondsError::OutOfRange),
+        ];
+
+        for (s, expected) in cases {
+            assert_eq!(Err(expected), parse_nanoseconds(s), "parsing {s:?}");
+        }
+    }
+
+    #[test]
+    fn TEST_parse_nanoseconds_ROUND_TRIPS_nanoseconds_to_string() {
+        for v in [0, 9, 89, 789, 6_000, 50_000, 400_000, 3_000_000, 20_000_000, 9_000_000_000, 200_000_000_000] {
+            assert_eq!(Ok(v), parse_nanoseconds(&nanoseconds_to_string(v, "")));
+            assert_eq!(Ok(-v), parse_nanoseconds(&nanoseconds_to_string(-v, "")));
+            assert_eq!(Ok(v), parse_nanoseconds(&nanoseconds_to_string(v, "+")));
+        }
+
+        // values with more than (around) three significant digits are
+        // truncated by formatting
+        for (v, expected) in [(6_789, 6_789), (123_456_789, 123_400_000), (9_123_456_789, 9_123_000_000)] {
+            assert_eq!(Ok(expected), parse_nanoseconds(&nanoseconds_to_string(v, "")));
+        }
+    }
+
+    #[test]
+    fn TEST_parse_nanoseconds_ROUND_TRIPS_COMPOUND() {
+        // formatting truncates, so parsing the result gives back a
+        // duration no greater, which formats identically
+        for v in [60_000_000_000, 125_399_999_999, 3_659_999_999_999, 100_800_000_000_001, i64::MAX, -86_399_999_999_999] {
+            let s = try_nanoseconds_to_string(v, "#").unwrap();
+            let parsed = parse_nanoseconds(&s).unwrap();
+
+            assert!(parsed.abs() <= v.abs(), "parsing {s:?}");
+            assert_eq!(s, try_nanoseconds_to_string(parsed, "#").unwrap());
+        }
+    }
+
+    #[test]
+    fn TEST_parse_nanoseconds_ROUND_TRIPS_FORMAT_SPECS() {
+        // formatting truncates, so parsing the result gives back a
+        // duration of the same sign and no greater magnitude, which
+        // formats identically (but for a negative value truncated to
+        // zero, such as `"-0.000ms"`)
+        for spec in ["", "+", "a", "#", "+#a", "12", "+a12", "#30", "ns", "24ns", "us", "~2", ".3ms", "+16.1s", "~5s"] {
+            for v in [0, 9, -789, 6_789, 1_050, -123_456_789, 3_063_260_000, 125_399_999_999, i64::MAX, i64::MIN + 1, i64::MIN] {
+                let s = try_nanoseconds_to_string(v, spec).unwrap();
+                let parsed = parse_nanoseconds(&s).unwrap_or_else(|e| panic!("parsing {s:?}: {e}"));
+
+                assert!(parsed.unsigned_abs() <= v.unsigned_abs(), "parsing {s:?}");
+                assert!(0 == parsed || parsed.signum() == v.signum(), "parsing {s:?}");
+                if 0 != parsed {
+                    assert_eq!(s, try_nanoseconds_to_string(parsed, spec).unwrap());
+                }
+            }
+        }
+
+        // in nanoseconds, nothing is truncated
+        for spec in ["ns", "+24ns"] {
+            for v in [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX] {
+                assert_eq!(Ok(v), parse_nanoseconds(&try_nanoseconds_to_string(v, spec).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn TEST_ParseNanosecondsError_Display() {
+        assert_eq!("missing or unrecognised duration unit", ParseNanosecondsError::InvalidUnit.to_string());
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,404 @@
+// src/diagnostics/wide_doomgram.rs : `WideDoomGram`
+
+use super::{
+    doomgram::{
+        DoomGram,
+        NUM_BUCKETS,
+    },
+    gram_utils,
+};
+
+use std::time::Duration;
+
+
+/// The minimum number of decades of a [`WideDoomGram`], being that of a
+/// [`DoomGram`].
+const MIN_DECADES : usize = NUM_BUCKETS;
+/// The maximum number of decades of a [`WideDoomGram`], being the number
+/// of decimal digits of `u64::MAX`.
+const MAX_DECADES : usize = 20;
+
+
+/// Decimal Order-Of-Magnitude frequency histoGRAM with a configurable
+/// number of decades, and an explicit bucket for zero-time events.
+///
+/// A [`DoomGram`] has twelve decade buckets - 1ns+, 10ns+, ..., 100s+ - and
+/// counts, but does not bucket, zero-time events. A [`WideDoomGram`] has a
+/// "zero" bucket, which receives events that took (to the precision of
+/// the clock) no time, followed by between 12 and 20 decade buckets, so
+/// that the times of long-running jobs are separated into the decades
+/// 1000s+ (~17 minutes), 10000s+ (~2.8 hours), and so on. As with
+/// [`DoomGram`], the last bucket is unbounded.
+///
+/// It has the same push API, overflow rules, and summary methods as
+/// [`DoomGram`] – which it maintains internally – and may be collapsed to
+/// a standard [`DoomGram`] via [`Self::as_doomgram()`] or
+/// [`Self::to_doomgram()`], or created from one via
+/// [`Self::from_doomgram()`].
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::WideDoomGram;
+///
+/// let mut wdg = WideDoomGram::new(16);
+///
+/// wdg.push_event_time_ns(0);
+/// wdg.push_event_time_ms(5);
+/// wdg.push_event_time_s(300);
+/// wdg.push_event_time_s(7_200);
+///
+/// assert_eq!("a______a____aa___", wdg.to_strip());
+/// assert_eq!("______a____a", wdg.to_doomgram().to_strip());
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+#[derive(Eq, PartialEq)]
+pub struct WideDoomGram {
+    doomgram : DoomGram,
+    /// The zero bucket, followed by one bucket per decade.
+    buckets :  Vec<u64>,
+}
+
+// API functions
+
+impl WideDoomGram {
+    /// Creates a new, empty, instance with the given number of decade
+    /// buckets (in addition to the zero bucket).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_decades` is less than 12 or greater than 20.
+    pub fn new(num_decades : usize) -> Self {
+        assert!(
+            (MIN_DECADES..=MAX_DECADES).contains(&num_decades),
+            "num_decades must be in the range [{MIN_DECADES}, {MAX_DECADES}], not {num_decades}",
+        );
+
+        Self {
+            doomgram : DoomGram::default(),
+            buckets :  vec![0; 1 + num_decades],
+        }
+    }
+
+    /// Creates an instance with the given number of decade buckets from
+    /// the events recorded in `dg`.
+    ///
+    /// The zero bucket receives the events that `dg` counts but does not
+    /// bucket. Since `dg` does not distinguish between the events in its
+    /// last (100s+) bucket, they are all placed in the 100s+ decade, even
+    /// when this instance has higher decades.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_decades` is less than 12 or greater than 20.
+    pub fn from_doomgram(
+        dg : &DoomGram,
+        num_decades : usize,
+    ) -> Self {
+        let mut wdg = Self::new(num_decades);
+
+        let dg_buckets = dg.buckets_();
+        let num_bucketed = dg_buckets.iter().fold(0u64, |acc, &n| acc.saturating_add(n));
+
+        wdg.buckets[0] = (dg.event_count() as u64).saturating_sub(num_bucketed);
+        wdg.buckets[1..=NUM_BUCKETS].copy_from_slice(&dg_buckets);
+        wdg.doomgram = dg.clone();
+
+        wdg
+    }
+}
+
+// Mutating methods
+
+impl WideDoomGram {
+    /// Clears the instance, resetting all values to the equivalent of a
+    /// newly constructed instance (with the same number of decades).
+    pub fn clear(&mut self) {
+        self.doomgram.clear();
+        self.buckets.iter_mut().for_each(|n| *n = 0);
+    }
+
+    /// Pushes an event with the given [`Duration`].
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &mut self,
+        duration : Duration,
+    ) -> bool {
+        self.push_event_time_ns(duration.as_nanos() as u64)
+    }
+
+    /// Pushes an event with the given number of nanoseconds.
+    pub fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        if self.doomgram.push_event_time_ns(time_in_ns) {
+            let index = (gram_utils::calc_doom(time_in_ns) as usize).min(self.buckets.len() - 1);
+
+            self.buckets[index] += 1;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Pushes an event with the given number of microseconds.
+    pub fn push_event_time_us(
+        &mut self,
+        time_in_us : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * time_in_us)
+    }
+
+    /// Pushes an event with the given number of milliseconds.
+    pub fn push_event_time_ms(
+        &mut self,
+        time_in_ms : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * time_in_ms)
+    }
+
+    /// Pushes an event with the given number of seconds.
+    pub fn push_event_time_s(
+        &mut self,
+        time_in_s : u64,
+    ) -> bool {
+        self.push_event_time_ns(1_000 * 1_000 * 1_000 * time_in_s)
+    }
+}
+
+// Non-mutating methods
+
+impl WideDoomGram {
+    /// The number of decade buckets (excluding the zero bucket).
+    pub fn num_decades(&self) -> usize {
+        self.buckets.len() - 1
+    }
+
+    /// The number of events counted in each bucket: first the zero bucket,
+    /// and then the decades 1ns+, 10ns+, ..., of which the last is
+    /// unbounded.
+    pub fn bucket_counts(&self) -> &[u64] {
+        &self.buckets
+    }
+
+    /// The number of zero-time events.
+    pub fn num_events_zero(&self) -> u64 {
+        self.buckets[0]
+    }
+
+    /// Borrows the standard (twelve decade) [`DoomGram`] equivalent of this
+    /// instance.
+    pub fn as_doomgram(&self) -> &DoomGram {
+        &self.doomgram
+    }
+
+    /// Collapses this instance into a standard (twelve decade)
+    /// [`DoomGram`].
+    pub fn to_doomgram(&self) -> DoomGram {
+        self.doomgram.clone()
+    }
+
+    /// Number of events counted.
+    pub fn event_count(&self) -> usize {
+        self.doomgram.event_count()
+    }
+
+    /// See [`DoomGram::event_time_total()`].
+    pub fn event_time_total(&self) -> Option<u64> {
+        self.doomgram.event_time_total()
+    }
+
+    /// See [`DoomGram::event_time_total_raw()`].
+    pub fn event_time_total_raw(&self) -> u64 {
+        self.doomgram.event_time_total_raw()
+    }
+
+    /// Indicates whether overflow has occurred.
+    pub fn has_overflowed(&self) -> bool {
+        self.doomgram.has_overflowed()
+    }
+
+    pub fn min_event_time(&self) -> Option<u64> {
+        self.doomgram.min_event_time()
+    }
+
+    pub fn max_event_time(&self) -> Option<u64> {
+        self.doomgram.max_event_time()
+    }
+
+    /// See [`DoomGram::to_mmm()`].
+    pub fn to_mmm(&self) -> String {
+        self.doomgram.to_mmm()
+    }
+
+    /// See [`DoomGram::to_nmmm()`].
+    pub fn to_nmmm(&self) -> String {
+        self.doomgram.to_nmmm()
+    }
+
+    /// Returns an ASCII strip for the histogram, with one character for
+    /// the zero bucket followed by one per decade, and so
+    /// `1 + num_decades()` characters in total.
+    ///
+    /// The character encoding is as described for [`DoomGram::to_strip()`].
+    pub fn to_strip(&self) -> String {
+        gram_utils::buckets_to_strip(&self.buckets)
+    }
+}
+
+// Trait implementations
+
+impl From<&WideDoomGram> for DoomGram {
+    fn from(value : &WideDoomGram) -> Self {
+        value.to_doomgram()
+    }
+}
+
+
+impl From<&DoomGram> for WideDoomGram {
+    /// Equivalent to [`WideDoomGram::from_doomgram()`] with 12 decades, and
+    /// so loses no information.
+    fn from(value : &DoomGram) -> Self {
+        Self::from_doomgram(value, MIN_DECADES)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::{
+        DoomGram,
+        WideDoomGram,
+    };
+
+
+    #[test]
+    fn TEST_WideDoomGram_new() {
+        let wdg = WideDoomGram::new(20);
+
+        assert_eq!(20, wdg.num_decades());
+        assert_eq!(21, wdg.bucket_counts().len());
+        assert_eq!(0, wdg.event_count());
+        assert_eq!("0:", wdg.to_nmmm());
+        assert_eq!("_".repeat(21), wdg.to_strip());
+    }
+
+    #[test]
+    #[should_panic(expected = "num_decades must be in the range [12, 20], not 11")]
+    fn TEST_WideDoomGram_new_TOO_FEW_DECADES() {
+        let _ = WideDoomGram::new(11);
+    }
+
+    #[test]
+    #[should_panic(expected = "num_decades must be in the range [12, 20], not 21")]
+    fn TEST_WideDoomGram_new_TOO_MANY_DECADES() {
+        let _ = WideDoomGram::new(21);
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_ZERO_BUCKET() {
+        let mut wdg = WideDoomGram::new(12);
+
+        for _ in 0..15 {
+            wdg.push_event_time_ns(0);
+        }
+        wdg.push_event_time_ns(1);
+
+        assert_eq!(15, wdg.num_events_zero());
+        assert_eq!(1, wdg.bucket_counts()[1]);
+        assert_eq!("ba___________", wdg.to_strip());
+        assert_eq!("a___________", wdg.as_doomgram().to_strip());
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_HIGH_DECADES() {
+        let mut wdg = WideDoomGram::new(20);
+
+        wdg.push_event_time_s(99);
+        wdg.push_event_time_s(100);
+        wdg.push_event_time_s(1_000);
+        wdg.push_event_time_s(10_000);
+        wdg.push_event_time_s(10_000);
+        wdg.push_event_time_ns(10_000_000_000_000_000_000);
+
+        assert_eq!("___________aaaa_____a", wdg.to_strip());
+        assert_eq!(
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 2, 0, 0, 0, 0, 0, 1],
+            wdg.bucket_counts(),
+        );
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_LAST_BUCKET_UNBOUNDED() {
+        let mut wdg = WideDoomGram::new(13);
+
+        wdg.push_event_time_s(100);
+        wdg.push_event_time_s(1_000);
+        wdg.push_event_time_s(100_000);
+
+        assert_eq!(&[1, 2], &wdg.bucket_counts()[12..]);
+        assert_eq!("____________aa", wdg.to_strip());
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_ROUND_TRIP_DoomGram() {
+        let mut dg = DoomGram::default();
+
+        for ns in [0, 0, 7, 70, 7_000, 7_000_000, 700_000_000_000] {
+            dg.push_event_time_ns(ns);
+        }
+
+        let wdg = WideDoomGram::from(&dg);
+
+        assert_eq!(12, wdg.num_decades());
+        assert_eq!(2, wdg.num_events_zero());
+        assert_eq!("aaa_a__a____a", wdg.to_strip());
+        assert_eq!(dg, DoomGram::from(&wdg));
+
+        // the 100s+ events are attributed to the 100s decade
+        let wdg = WideDoomGram::from_doomgram(&dg, 16);
+
+        assert_eq!("aaa_a__a____a____", wdg.to_strip());
+        assert_eq!(dg.to_nmmm(), wdg.to_nmmm());
+        assert_eq!(dg, wdg.to_doomgram());
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_MATCHES_DoomGram() {
+        let mut wdg = WideDoomGram::new(14);
+        let mut dg = DoomGram::default();
+
+        for ns in [3, 33, 333, 3_333, 33_333_333, 3_333_333_333_333] {
+            assert_eq!(dg.push_event_time_ns(ns), wdg.push_event_time_ns(ns));
+        }
+
+        assert_eq!(&dg, wdg.as_doomgram());
+        assert_eq!("_aaaa___a____a_", wdg.to_strip());
+    }
+
+    #[test]
+    fn TEST_WideDoomGram_OVERFLOW_AND_clear() {
+        let mut wdg = WideDoomGram::new(20);
+
+        assert!(wdg.push_event_time_ns(u64::MAX));
+        assert!(!wdg.push_event_time_ns(1));
+
+        assert!(wdg.has_overflowed());
+        assert_eq!(1, wdg.bucket_counts()[20]);
+        assert_eq!("1:OVERFLOW", wdg.to_nmmm());
+
+        wdg.clear();
+
+        assert_eq!(WideDoomGram::new(20), wdg);
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -0,0 +1,336 @@
+// src/diagnostics/windowed_doomgram.rs : `WindowedDoomGram`
+
+use super::{
+    clock::{
+        Clock,
+        InstantClock,
+    },
+    doomgram::DoomGram,
+};
+
+use std::time::Duration;
+
+
+/// A [`DoomGram`] over a rolling time window, comprising a ring of
+/// per-interval grams ("slots"), such that recent events are not hidden
+/// by the weight of earlier ones.
+///
+/// Time is measured by a [`Clock`] - [`InstantClock`] unless created by
+/// [`Self::with_clock()`] - and slots are reused as time advances, so that
+/// the instance holds at most the last `num_slots` intervals.
+///
+/// # Examples
+///
+/// ```
+/// use diagnosticism::{
+///     ManualClock,
+///     WindowedDoomGram,
+/// };
+///
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new(0);
+///
+/// // the last 60 one-second slots
+/// let mut wdg = WindowedDoomGram::with_clock(60, Duration::from_secs(1), &clock);
+///
+/// wdg.push_event_time_ms(300);
+///
+/// clock.advance(Duration::from_secs(10));
+///
+/// wdg.push_event_time_ms(2);
+///
+/// assert_eq!("1:2ms", wdg.merged_last(5).to_nmmm());
+/// assert_eq!("2:2ms-151ms-300ms", wdg.merged().to_nmmm());
+///
+/// clock.advance(Duration::from_secs(55));
+///
+/// assert_eq!("1:2ms", wdg.merged().to_nmmm());
+/// ```
+#[derive(Clone)]
+#[derive(Debug)]
+pub struct WindowedDoomGram<C : Clock = InstantClock> {
+    clock :            C,
+    slot_duration_ns : u64,
+    /// Each slot's gram, along with the (absolute) interval number to
+    /// which it pertains.
+    slots :            Vec<(u64, DoomGram)>,
+}
+
+// API functions
+
+impl WindowedDoomGram {
+    /// Creates an instance with the given number of slots, each of the
+    /// given duration, that measures time with [`InstantClock`].
+    ///
+    /// # Panics
+    ///
+    /// If `num_slots` is 0, or if `slot_duration` is less than 1ns.
+    pub fn new(
+        num_slots : usize,
+        slot_duration : Duration,
+    ) -> Self {
+        Self::with_clock(num_slots, slot_duration, InstantClock)
+    }
+}
+
+impl<C : Clock> WindowedDoomGram<C> {
+    /// Creates an instance with the given number of slots, each of the
+    /// given duration, that measures time with the given [`Clock`].
+    ///
+    /// # Panics
+    ///
+    /// If `num_slots` is 0, or if `slot_duration` is less than 1ns.
+    pub fn with_clock(
+        num_slots : usize,
+        slot_duration : Duration,
+        clock : C,
+    ) -> Self {
+        assert!(0 != num_slots, "`num_slots` must be at least 1");
+
+        let slot_duration_ns = slot_duration.as_nanos().min(u128::from(u64::MAX)) as u64;
+
+        assert!(0 != slot_duration_ns, "`slot_duration` must be at least 1ns");
+
+        Self {
+            clock,
+            slot_duration_ns,
+            slots : vec![(0, DoomGram::default()); num_slots],
+        }
+    }
+}
+
+// Mutating methods
+
+impl<C : Clock> WindowedDoomGram<C> {
+    /// Clears all slots.
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|(interval, dg)| {
+            *interval = 0;
+            dg.clear();
+        });
+    }
+
+    /// Pushes an event with the given [`Duration`] into the current slot.
+    ///
+    /// # Note:
+    /// The value obtained from `Duration#as_nanos()` is truncated to `u64`.
+    pub fn push_event_duration(
+        &mut self,
+        duration : Duration,
+    ) -> bool {
+        self.push_event_time_ns(duration.as_nanos() as u64)
+    }
+
+    /// Pushes an event with the given number of nanoseconds into the
+    /// current slot.
+    pub fn push_event_time_ns(
+        &mut self,
+        time_in_ns : u64,
+    ) -> bool {
+        self.current_slot_().push_event_time_ns(time_in_ns)
+    }
+
+    /// Pushes an event with the given number of microseconds into the
+    /// current slot.
+    pub fn push_event_time_us(
+        &mut self,
+        time_in_us : u64,
+    ) -> bool {
+        self.current_slot_().push_event_time_us(time_in_us)
+    }
+
+    /// Pushes an event with the given number of milliseconds into the
+    /// current slot.
+    pub fn push_event_time_ms(
+        &mut self,
+        time_in_ms : u64,
+    ) -> bool {
+        self.current_slot_().push_event_time_ms(time_in_ms)
+    }
+
+    /// Pushes an event with the given number of seconds into the current
+    /// slot.
+    pub fn push_event_time_s(
+        &mut self,
+        time_in_s : u64,
+    ) -> bool {
+        self.current_slot_().push_event_time_s(time_in_s)
+    }
+}
+
+// Non-mutating methods
+
+impl<C : Clock> WindowedDoomGram<C> {
+    /// The number of slots.
+    pub fn num_slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The duration of each slot.
+    pub fn slot_duration(&self) -> Duration {
+        Duration::from_nanos(self.slot_duration_ns)
+    }
+
+    /// Obtains a [`DoomGram`] of all events in the window, i.e. those
+    /// pushed within the current slot and the preceding `num_slots - 1`.
+    pub fn merged(&self) -> DoomGram {
+        self.merged_last(self.slots.len())
+    }
+
+    /// Obtains a [`DoomGram`] of the events pushed within the current slot
+    /// and the preceding `num_slots - 1` (at most).
+    ///
+    /// For example, with one-second slots, `merged_last(5)` obtains the
+    /// events of (between 4 and 5 of) the last 5 seconds.
+    pub fn merged_last(
+        &self,
+        num_slots : usize,
+    ) -> DoomGram {
+        let now_interval = self.current_interval_();
+        let num_slots = num_slots.min(self.slots.len()) as u64;
+
+        let mut merged = DoomGram::default();
+
+        for (interval, dg) in &self.slots {
+            if *interval <= now_interval && now_interval - *interval < num_slots {
+                merged.merge(dg);
+            }
+        }
+
+        merged
+    }
+}
+
+// Implementation
+
+impl<C : Clock> WindowedDoomGram<C> {
+    fn current_interval_(&self) -> u64 {
+        self.clock.now_ns() / self.slot_duration_ns
+    }
+
+    fn current_slot_(&mut self) -> &mut DoomGram {
+        let now_interval = self.current_interval_();
+        let index = (now_interval % self.slots.len() as u64) as usize;
+
+        let (interval, dg) = &mut self.slots[index];
+
+        if *interval != now_interval {
+            *interval = now_interval;
+            dg.clear();
+        }
+
+        dg
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #![allow(non_snake_case)]
+
+    use super::WindowedDoomGram;
+
+    use crate::diagnostics::ManualClock;
+
+    use std::time::Duration;
+
+
+    #[test]
+    fn TEST_WindowedDoomGram_EMPTY() {
+        let wdg = WindowedDoomGram::new(60, Duration::from_secs(1));
+
+        assert_eq!(60, wdg.num_slots());
+        assert_eq!(Duration::from_secs(1), wdg.slot_duration());
+        assert_eq!("0:", wdg.merged().to_nmmm());
+        assert_eq!("0:", wdg.merged_last(1).to_nmmm());
+    }
+
+    #[test]
+    fn TEST_WindowedDoomGram_SLOTS_EXPIRE() {
+        let clock = ManualClock::new(0);
+        let mut wdg = WindowedDoomGram::with_clock(3, Duration::from_millis(100), &clock);
+
+        // slot 0
+        wdg.push_event_time_ns(1);
+
+        clock.advance_ns(100_000_000);
+
+        // slot 1
+        wdg.push_event_time_ns(20);
+        wdg.push_event_time_ns(30);
+
+        clock.advance_ns(150_000_000);
+
+        // slot 2 (at 250ms)
+        wdg.push_event_time_ns(400);
+
+        assert_eq!("4:1ns-112ns-400ns", wdg.merged().to_nmmm());
+        assert_eq!("3:20ns-150ns-400ns", wdg.merged_last(2).to_nmmm());
+        assert_eq!("1:400ns", wdg.merged_last(1).to_nmmm());
+        assert_eq!("0:", wdg.merged_last(0).to_nmmm());
+        assert_eq!(wdg.merged(), wdg.merged_last(100));
+
+        clock.advance_ns(100_000_000);
+
+        // slot 3 (at 350ms) reuses slot 0's storage
+        wdg.push_event_time_ns(5_000);
+
+        assert_eq!("4:20ns-1.362µs-5µs", wdg.merged().to_nmmm());
+
+        // queries do not depend on subsequent pushes
+        clock.advance_ns(200_000_000);
+
+        assert_eq!("1:5µs", wdg.merged().to_nmmm());
+
+        clock.advance_ns(1_000_000_000);
+
+        assert_eq!("0:", wdg.merged().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_WindowedDoomGram_LARGE_JUMP() {
+        let clock = ManualClock::new(0);
+        let mut wdg = WindowedDoomGram::with_clock(4, Duration::from_secs(1), &clock);
+
+        wdg.push_event_time_ms(1);
+
+        // an exact multiple of the window, mapping to the same slot
+        clock.advance(Duration::from_secs(8));
+
+        wdg.push_event_time_ms(2);
+
+        assert_eq!("1:2ms", wdg.merged().to_nmmm());
+    }
+
+    #[test]
+    fn TEST_WindowedDoomGram_clear() {
+        let clock = ManualClock::new(7_000_000_000);
+        let mut wdg = WindowedDoomGram::with_clock(2, Duration::from_secs(1), &clock);
+
+        wdg.push_event_time_us(3);
+        wdg.push_event_duration(Duration::from_micros(4));
+        wdg.push_event_time_s(1);
+
+        assert_eq!(3, wdg.merged().event_count());
+
+        wdg.clear();
+
+        assert_eq!("0:", wdg.merged().to_nmmm());
+    }
+
+    #[test]
+    #[should_panic(expected = "`num_slots` must be at least 1")]
+    fn TEST_WindowedDoomGram_ZERO_SLOTS() {
+        let _ = WindowedDoomGram::new(0, Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "`slot_duration` must be at least 1ns")]
+    fn TEST_WindowedDoomGram_ZERO_DURATION() {
+        let _ = WindowedDoomGram::new(1, Duration::ZERO);
+    }
+}
+
+
+// ///////////////////////////// end of file //////////////////////////// //
@@ -13,8 +13,9 @@
 //! * **[`Debug`](std::fmt::Debug) helpers** — control what appears in
 //!   log output ([`Ellipsis`], [`Password`], [`DebugSqueezer`]);
 //! * **Timing** — record duration distributions ([`DoomGram`]),
-//!   measure closures ([`doom_scope`]), and format durations via
-//!   [`nanoseconds_to_string`] into [`NanosecondsStr`];
+//!   measure closures ([`doom_scope`]), format durations via
+//!   [`nanoseconds_to_string`] into [`NanosecondsStr`], and parse them
+//!   back via [`parse_nanoseconds`];
 //! * **Source location** — compile-time file, line, and function
 //!   macros (`fileline!`, `filelinefunction!`, and others).
 //!
@@ -39,25 +40,85 @@
 //! The following are re-exported at the crate root (and also available in
 //! [`diagnostics`]):
 //!
+//! * [`AtomicDoomGram`] — thread-safe, lock-free [`DoomGram`] equivalent,
+//!   from which a consistent [`DoomGram`] snapshot may be taken;
+//! * [`Clock`] — trait for sources of time from which elapsed times are
+//!   measured, implemented by [`InstantClock`] (wall-clock),
+//!   [`ManualClock`] (for deterministic tests), and, on 64-bit Linux
+//!   only, `ThreadCpuClock` and `ProcessCpuClock` (CPU time);
 //! * [`DebugSqueezer`] — restrict the length of
 //!   [`Debug`](std::fmt::Debug) output for individual fields;
 //! * [`DoomGram`] — decimal order-of-magnitude histogram with a compact
 //!   12-character strip for logging, plus [`DoomGram::to_mmm`] and
-//!   [`DoomGram::to_nmmm`] min/mean/max duration summaries;
+//!   [`DoomGram::to_nmmm`] min/mean/max duration summaries, and
+//!   [`DoomGram::stddev`] and [`DoomGram::to_mmms`] to report jitter;
+//! * [`DoomGramBarChart`] — multi-line bar chart of a [`DoomGram`],
+//!   obtained from [`DoomGram::bar_chart`], with [`ChartScale`] linear or
+//!   logarithmic scaling, along with [`DoomGram::to_sparkline`];
+//! * [`DoomGramRegistry`] — thread-safe collection of named
+//!   [`DoomGram`]s (global or scoped), with sorted report output;
+//! * [`DoomGramSummary`] — count, min, mean, and max parsed from a
+//!   [`DoomGram::to_mmm`] or [`DoomGram::to_nmmm`] string;
+//! * [`DoomGramQuantile`] — an order-of-magnitude accurate quantile
+//!   estimate, obtained from [`DoomGram::quantile`];
+//! * [`DoomFuture`], [`doom_future`], and [`doom_future_with_clock`] —
+//!   time a future from first poll to completion, recording into any
+//!   [`DoomTarget`];
+//! * [`DoomGuard`] — scope guard, obtained from
+//!   [`DoomGram::time_scope`], that records the elapsed time into a
+//!   [`DoomGram`] when dropped;
+//! * [`DoomTarget`] — trait for grams into which measured event times may
+//!   be recorded;
+//! * [`DurationFormat`] — options for formatting durations with a fixed
+//!   [`DurationUnit`], precision, and width, parsed from a format
+//!   specification (failing with [`ParseDurationFormatError`]);
 //! * [`Ellipsis`] — emit `"..."` for redacted
 //!   [`Debug`](std::fmt::Debug) fields;
+//! * [`ExemplarDoomGram`] — [`DoomGram`] that also retains the slowest N
+//!   events, each with a caller-supplied tag, as [`DoomExemplar`]s;
+//! * [`FmtDuration`] and [`FmtElapsed`] — [`Display`](std::fmt::Display)
+//!   adapters for a [`Duration`](std::time::Duration) and the time
+//!   elapsed since an [`Instant`](std::time::Instant);
+//! * [`FineDoomGram`] — [`DoomGram`] with a configurable number of
+//!   sub-buckets per decade;
+//! * [`OomGram`] — order-of-magnitude histogram for quantities of any
+//!   unit, whose summaries are formatted by an [`OomFormatter`] such as
+//!   [`BytesFormatter`], [`CountFormatter`], or [`DurationFormatter`];
+//! * [`OutcomeDoomGram`] — pair of [`DoomGram`]s recording successful
+//!   and failed events separately, with [`doom_scope_result`] and
+//!   [`OutcomeDoomGuard`] to route measurements by outcome;
 //! * [`Password`] — emit a run of `*` characters for sensitive
 //!   [`Debug`](std::fmt::Debug) fields;
+//! * [`WideDoomGram`] — [`DoomGram`] with up to 20 decades and an
+//!   explicit zero bucket;
+//! * [`WindowedDoomGram`] — [`DoomGram`] over a rolling time window of
+//!   per-interval slots;
 //! * [`doom_scope`] — time a closure and record the elapsed duration in a
-//!   [`DoomGram`];
+//!   [`DoomGram`], and [`doom_scope_with_clock`] to do so with a given
+//!   [`Clock`];
 //! * [`NanosecondsStr`] — compact storage for a formatted duration string;
 //! * [`nanoseconds_to_string`] — format a nanosecond count into a
-//!   [`NanosecondsStr`];
+//!   [`NanosecondsStr`], or, without allocating, to any
+//!   [`fmt::Write`](std::fmt::Write) via [`write_nanoseconds`] or into a
+//!   byte buffer via [`format_nanoseconds_into`], and
+//!   [`try_nanoseconds_to_string`] to do so according to the format-spec
+//!   mini-language of [`DurationFormat`];
+//! * [`parse_doomgram_strip`], [`parse_doomgram_mmm`], and
+//!   [`parse_doomgram_nmmm`] — parse [`DoomGram`] strings back into
+//!   structured values (failing with [`ParseDoomGramError`]);
+//! * [`parse_nanoseconds`] — parse a duration string, in any of the forms
+//!   produced by [`nanoseconds_to_string`], into a nanosecond count
+//!   (failing with [`ParseNanosecondsError`]);
+//! * [`write_doomgram_prometheus`] and [`doomgram_to_prometheus`] — write
+//!   a [`DoomGram`] as a histogram in the Prometheus text exposition
+//!   format (failing with [`WritePrometheusError`]);
 //!
 //! ## Macros (crate root)
 //!
-//! File, line, and function helpers are exported at the crate root:
+//! The following macros are exported at the crate root:
 //!
+//! * [`doom_registry_scope!`] — time a closure into a named gram of a
+//!   [`DoomGramRegistry`], named by default for the enclosing function;
 //! * [`fileline!`] — file name and line number at the call site;
 //! * [`filelinefunction!`] — file, line, and unqualified function name;
 //! * [`filelinefunction_fully_qualified_name!`] — file, line, and
@@ -96,13 +157,64 @@ pub mod diagnostics;
 pub(crate) mod macros;
 
 pub use diagnostics::{
+    doom_future,
+    doom_future_with_clock,
     doom_scope,
+    doom_scope_result,
+    doom_scope_result_with_clock,
+    doom_scope_with_clock,
+    doomgram_to_prometheus,
+    format_nanoseconds_into,
     nanoseconds_to_string,
+    parse_doomgram_mmm,
+    parse_doomgram_nmmm,
+    parse_doomgram_strip,
+    parse_nanoseconds,
+    try_nanoseconds_to_string,
+    write_doomgram_prometheus,
+    write_nanoseconds,
+    AtomicDoomGram,
+    BytesFormatter,
+    ChartScale,
+    Clock,
+    CountFormatter,
     DebugSqueezer,
+    DoomExemplar,
+    DoomFuture,
     DoomGram,
+    DoomGramBarChart,
+    DoomGramQuantile,
+    DoomGramRegistry,
+    DoomGramSummary,
+    DoomGuard,
+    DoomTarget,
+    DurationFormat,
+    DurationFormatter,
+    DurationUnit,
     Ellipsis,
+    ExemplarDoomGram,
+    FineDoomGram,
+    FmtDuration,
+    FmtElapsed,
+    InstantClock,
+    ManualClock,
     NanosecondsStr,
+    OomFormatter,
+    OomGram,
+    OutcomeDoomGram,
+    OutcomeDoomGuard,
+    ParseDoomGramError,
+    ParseDurationFormatError,
+    ParseNanosecondsError,
     Password,
+    WideDoomGram,
+    WindowedDoomGram,
+    WritePrometheusError,
+};
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+pub use diagnostics::{
+    ProcessCpuClock,
+    ThreadCpuClock,
 };
 
 